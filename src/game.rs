@@ -1,12 +1,20 @@
 //! Module for noughts and crosses game
+pub mod accounts;
+pub mod clock;
 pub mod converters;
+pub mod lobby;
+pub mod nickname;
 pub mod playboard;
 pub mod player_manager;
+pub mod rating;
+pub mod scoreboard;
+pub mod starting_policy;
+pub mod tournament;
 
-use player_manager::PlayerTrait;
+use player_manager::{ClientId, MsgToPlayer, PlayerInfo, PlayerManagerTrait, PlayerTrait, Role};
 
 /// Enum for game stage
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 enum GameStage {
     /// Waiting for both players
     WaitingForPlayers,
@@ -14,14 +22,22 @@ enum GameStage {
     WaitingForPlayer(PlayerId),
     /// Both players are available - player is on move
     PlayerOnMove(PlayerId),
+    /// First move was made - player decides between swap of sides and own move
+    SwapDecision(PlayerId),
+    /// Game ended - players decide if they want to play again
+    RematchVoting,
+    /// Game is interrupted - waiting for reconnection of specific player
+    WaitingForReconnection(PlayerId),
 }
 
 /// Enum for player id
-#[derive(Debug, PartialEq, Clone, Copy, Hash, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Hash, Eq, serde::Serialize, serde::Deserialize)]
 pub enum PlayerId {
     /// Player playing for circle
+    #[serde(rename = "o")]
     Circle,
     /// Player playing for cross
+    #[serde(rename = "x")]
     Cross,
 }
 
@@ -48,22 +64,1220 @@ impl std::ops::Not for PlayerId {
     }
 }
 
+/// Enum for reason of game result
+#[derive(Debug, PartialEq, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultReason {
+    /// Player completed line
+    LineCompleted,
+    /// Nobody is able to complete line
+    BoardExhausted,
+    /// Player resigned
+    Resignation,
+    /// Players agreed on draw
+    Agreement,
+    /// Player ran out of time
+    Timeout,
+}
+
+/// Enum for commands sended by player
+pub enum PlayerCommand<T> {
+    /// Make move on given position
+    Move(T),
+    /// Swap sides with other player (pie rule)
+    Swap,
+    /// Positive answer to question
+    Yes,
+    /// Negative answer to question
+    No,
+    /// Give up current game
+    Resign,
+    /// Offer draw to other player (or accept offer of other player)
+    Draw,
+    /// Continue interrupted game using session token
+    Resume(String),
+    /// Choose nickname
+    Name(String),
+    /// Show best rated players
+    Leaderboard,
+}
+
+/// Result of finished game reported to owner of game
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct GameRecord {
+    /// Client playing for circle
+    pub circle: ClientId,
+    /// Client playing for cross
+    pub cross: ClientId,
+    /// Client who won game - None for draw
+    pub winner: Option<ClientId>,
+    pub reason: ResultReason,
+}
+
+/// Settings of game rules
+#[derive(Clone, Default)]
+pub struct Settings {
+    /// Second player may swap sides after first move
+    pub pie_rule: bool,
+    /// Policy selecting player starting new game
+    pub starting_policy: starting_policy::StartingPolicy,
+    /// Number of games in match - None for endless match
+    pub match_length: Option<usize>,
+    /// Time limits of players
+    pub time_control: clock::TimeControl,
+    /// Period of sending remaining time to players - None for no updates
+    pub clock_updates: Option<std::time::Duration>,
+    /// Time for reconnection of disconnected player - None for immediate end of game
+    pub reconnect_grace: Option<std::time::Duration>,
+    /// Maximal number of spectators watching game
+    pub spectator_limit: usize,
+    /// Queue connections over limit as next challengers instead of rejecting them
+    pub queue_challengers: bool,
+    /// Ratings updated by games of logged in players - None for unrated games
+    pub ratings: Option<rating::RatedPool>,
+    /// Channel receiving results of games - finished match is not followed by rematch
+    pub results: Option<tokio::sync::mpsc::UnboundedSender<GameRecord>>,
+}
+
+/// Seat of disconnected player held for reconnection
+struct HeldSeat {
+    /// Client id of disconnected player
+    client_id: ClientId,
+    /// Nickname of disconnected player
+    nickname: Option<String>,
+    /// Account of disconnected player
+    account: Option<String>,
+    /// Session token of disconnected player
+    token: String,
+    /// Time when player forfeits game
+    deadline: tokio::time::Instant,
+    /// Stage of game before player disconnected
+    interrupted_stage: GameStage,
+}
+
+/// Generate random session token
+pub fn generate_session_token() -> String {
+    use rand::Rng;
+
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(12)
+        .map(char::from)
+        .collect()
+}
+
+/// Representation of playboard used by players of player manager
+type FieldRepresentation<T> =
+    <<T as PlayerManagerTrait>::NewPlayer as PlayerTrait>::FieldRepresentation;
+
+/// State of running game
+struct Game<T, R, U, V, W>
+where
+    T: PlayerManagerTrait,
+{
+    player_manager: T,
+    settings: Settings,
+    create_pb: U,
+    convert_player_msg_to_command: V,
+    convert_pb_for_pm: W,
+    game_stage: GameStage,
+    playboard: R,
+    /// Number of moves on current playboard
+    moves_count: usize,
+    starting_player: starting_policy::StartingPlayerSelector,
+    clocks: clock::Clocks,
+    scoreboard: scoreboard::Scoreboard,
+    /// Player who offered draw in current game
+    draw_offer: Option<PlayerId>,
+    /// Clients who accepted rematch
+    rematch_votes: std::collections::HashSet<ClientId>,
+    /// Session tokens of players
+    session_tokens: std::collections::HashMap<ClientId, String>,
+    /// Seat of disconnected player
+    held_seat: Option<HeldSeat>,
+    /// Clients who joined during interrupted game and have to present session token
+    pending_players: std::collections::HashMap<ClientId, T::NewPlayer>,
+    /// Clients who have free seat and have to choose nickname
+    naming_players: std::collections::HashMap<ClientId, T::NewPlayer>,
+    players: std::collections::HashMap<PlayerId, T::NewPlayer>,
+    spectators: std::collections::HashMap<ClientId, T::NewPlayer>,
+    /// Clients waiting for free seat
+    challengers: std::collections::VecDeque<T::NewPlayer>,
+}
+
+impl<T, R, U, V, W> Game<T, R, U, V, W>
+where
+    T: PlayerManagerTrait,
+    R: playboard::Playboard,
+    U: Fn() -> Result<R, Box<dyn std::error::Error>>,
+    V: Fn(T::PlayerMsg) -> converters::ConversionResult<PlayerCommand<R::Position>>,
+    W: Fn(&R) -> FieldRepresentation<T>,
+{
+    /// Find id of player connected as client
+    fn find_player_id(&self, client_id: ClientId) -> Option<PlayerId> {
+        self.players
+            .iter()
+            .find(|(_, player)| player.get_client_id() == client_id)
+            .map(|(player_id, _)| *player_id)
+    }
+
+    /// Get client ids of players playing for circle and cross
+    fn get_client_ids(&self) -> (ClientId, ClientId) {
+        (
+            self.players[&PlayerId::Circle].get_client_id(),
+            self.players[&PlayerId::Cross].get_client_id(),
+        )
+    }
+
+    /// Get nickname of player (or its symbol if player has no nickname)
+    fn get_nickname(&self, player_id: PlayerId) -> String {
+        self.players
+            .get(&player_id)
+            .and_then(|player| player.get_nickname())
+            .map(|nickname| nickname.to_owned())
+            .unwrap_or_else(|| player_id.to_string())
+    }
+
+    /// Get account of player (including disconnected player waited for)
+    fn get_account(&self, player_id: PlayerId) -> Option<String> {
+        match (
+            self.players.get(&player_id),
+            &self.held_seat,
+            self.game_stage,
+        ) {
+            (Some(player), _, _) => player.get_account().map(|x| x.to_owned()),
+            (None, Some(held_seat), GameStage::WaitingForReconnection(x)) if x == player_id => {
+                held_seat.account.clone()
+            }
+            _ => None,
+        }
+    }
+
+    /// Find connected client regardless of its role
+    fn find_client_mut(&mut self, client_id: ClientId) -> Option<&mut T::NewPlayer> {
+        self.players
+            .values_mut()
+            .chain(self.spectators.values_mut())
+            .chain(self.pending_players.values_mut())
+            .chain(self.naming_players.values_mut())
+            .chain(self.challengers.iter_mut())
+            .find(|player| player.get_client_id() == client_id)
+    }
+
+    /// Get nickname and rating of player
+    fn get_player_info(&self, player_id: PlayerId) -> PlayerInfo {
+        let account = self
+            .players
+            .get(&player_id)
+            .and_then(|player| player.get_account());
+
+        PlayerInfo {
+            nickname: self.get_nickname(player_id),
+            rating: match (&self.settings.ratings, account) {
+                (Some(ratings), Some(account)) => Some(ratings.store.get(&ratings.pool, account)),
+                _ => None,
+            },
+        }
+    }
+
+    /// Send best rated accounts of pool of this game to client
+    async fn send_leaderboard(&mut self, client_id: ClientId) {
+        let leaderboard = self
+            .settings
+            .ratings
+            .as_ref()
+            .map(|ratings| ratings.store.leaderboard(&ratings.pool))
+            .unwrap_or_default();
+
+        if let Some(player) = self.find_client_mut(client_id) {
+            player
+                .send_msg_to_player(MsgToPlayer::Leaderboard(leaderboard))
+                .await;
+        }
+    }
+
+    /// Update ratings of logged in players after game
+    async fn update_ratings(
+        &mut self,
+        accounts: (Option<String>, Option<String>),
+        winner: Option<PlayerId>,
+    ) {
+        let ratings = match &self.settings.ratings {
+            Some(x) => x.clone(),
+            None => return,
+        };
+
+        let (circle, cross) = match accounts {
+            (Some(circle), Some(cross)) if circle != cross => (circle, cross),
+            // Games of guests are not rated
+            _ => return,
+        };
+
+        let score = match winner {
+            Some(PlayerId::Circle) => 1.0,
+            Some(PlayerId::Cross) => 0.0,
+            None => 0.5,
+        };
+
+        match ratings
+            .store
+            .record_game(&ratings.pool, &circle, &cross, score)
+            .await
+        {
+            Ok((circle_change, cross_change)) => {
+                log::info!(
+                    "Ratings in pool {} updated - {}: {} ({:+}), {}: {} ({:+})",
+                    ratings.pool,
+                    circle,
+                    circle_change.rating,
+                    circle_change.change,
+                    cross,
+                    cross_change.rating,
+                    cross_change.change
+                );
+
+                self.send_to(PlayerId::Circle, MsgToPlayer::RatingChanged(circle_change))
+                    .await;
+                self.send_to(PlayerId::Cross, MsgToPlayer::RatingChanged(cross_change))
+                    .await;
+            }
+            Err(err) => log::error!("Unable to save ratings: {}", err),
+        }
+    }
+
+    /// Check if some seat is not taken by player or client choosing nickname
+    fn has_free_seat(&self) -> bool {
+        matches!(
+            self.game_stage,
+            GameStage::WaitingForPlayers | GameStage::WaitingForPlayer(_)
+        ) && self.players.len() + self.naming_players.len() < 2
+    }
+
+    /// Send message to player
+    async fn send_to(&mut self, player_id: PlayerId, msg: MsgToPlayer<FieldRepresentation<T>>) {
+        if let Some(player) = self.players.get_mut(&player_id) {
+            player.send_msg_to_player(msg).await;
+        }
+    }
+
+    /// Send current playboard to all players and spectators
+    async fn broadcast_playboard(&mut self) {
+        for player in self
+            .players
+            .values_mut()
+            .chain(self.spectators.values_mut())
+        {
+            player
+                .send_msg_to_player(MsgToPlayer::Playboard((self.convert_pb_for_pm)(
+                    &self.playboard,
+                )))
+                .await;
+        }
+    }
+
+    /// Inform players about number of spectators
+    async fn broadcast_spectators_count(&mut self) {
+        let count = self.spectators.len();
+
+        for player in self.players.values_mut() {
+            player
+                .send_msg_to_player(MsgToPlayer::Spectators(count))
+                .await;
+        }
+    }
+
+    /// Replace playboard by new one
+    fn reset_playboard(&mut self) {
+        self.playboard = (self.create_pb)().unwrap();
+        self.moves_count = 0;
+        self.draw_offer = None;
+    }
+
+    /// Start game on new playboard
+    async fn start_new_game(&mut self) {
+        self.reset_playboard();
+        self.clocks.reset();
+
+        let (circle, cross) = self.get_client_ids();
+        let starter = self.starting_player.select(circle, cross);
+
+        self.broadcast_playboard().await;
+
+        self.game_stage = GameStage::PlayerOnMove(starter);
+        self.announce_player_on_move(starter).await;
+    }
+
+    /// Inform players who is on move
+    async fn announce_player_on_move(&mut self, player_on_move: PlayerId) {
+        if let GameStage::SwapDecision(_) = self.game_stage {
+            self.send_to(player_on_move, MsgToPlayer::SwapAvailable)
+                .await;
+        }
+
+        self.send_to(player_on_move, MsgToPlayer::YourAreOnMove)
+            .await;
+        let nickname = self.get_nickname(player_on_move);
+        self.send_to(!player_on_move, MsgToPlayer::OtherPlayerIsOnMove(nickname))
+            .await;
+
+        self.clocks.start(player_on_move);
+    }
+
+    /// Send remaining time to all players
+    async fn broadcast_clocks(&mut self) {
+        let state = self.clocks.get_state();
+
+        for player in self
+            .players
+            .values_mut()
+            .chain(self.spectators.values_mut())
+        {
+            player.send_msg_to_player(MsgToPlayer::Clock(state)).await;
+        }
+    }
+
+    /// Player on move ran out of time
+    async fn handle_timeout(&mut self) {
+        if let GameStage::PlayerOnMove(player_on_move) | GameStage::SwapDecision(player_on_move) =
+            self.game_stage
+        {
+            self.broadcast_clocks().await;
+            self.finish_game(Some(!player_on_move), ResultReason::Timeout)
+                .await;
+        }
+    }
+
+    async fn handle_join(&mut self, client_id: ClientId, new_player_data: T::NewPlayerData) {
+        let player_id = match self.game_stage {
+            GameStage::WaitingForPlayers if self.has_free_seat() => PlayerId::Circle,
+            GameStage::WaitingForPlayer(player_id) if self.has_free_seat() => player_id,
+            GameStage::WaitingForReconnection(player_id) => player_id,
+            _ if self.spectators.len() < self.settings.spectator_limit => {
+                return self.add_spectator(client_id, new_player_data).await;
+            }
+            _ => {
+                let player = self.player_manager.create_new_player(
+                    client_id,
+                    Role::Challenger,
+                    new_player_data,
+                );
+                return self.queue_or_reject(player).await;
+            }
+        };
+
+        let mut player = self.player_manager.create_new_player(
+            client_id,
+            Role::Player(player_id),
+            new_player_data,
+        );
+        log::info!("Client {} joined as {}", client_id, player.get_role());
+        player.send_msg_to_player(MsgToPlayer::WelcomePlayer).await;
+
+        match self.game_stage {
+            GameStage::WaitingForReconnection(_) => {
+                player
+                    .send_msg_to_player(MsgToPlayer::EnterSessionToken)
+                    .await;
+                self.pending_players.insert(client_id, player);
+            }
+            _ => self.ask_nickname(player).await,
+        }
+    }
+
+    /// Ask player for nickname before seating (player with nickname chosen before joining keeps
+    /// it and logged in player uses name of account)
+    async fn ask_nickname(&mut self, mut player: T::NewPlayer) {
+        let client_id = player.get_client_id();
+        let nickname = player.get_nickname().or(player.get_account());
+
+        match nickname.map(|x| x.to_owned()) {
+            Some(nickname) => {
+                self.naming_players.insert(client_id, player);
+                self.handle_nickname(client_id, Ok(PlayerCommand::Name(nickname)))
+                    .await;
+            }
+            None => {
+                player.send_msg_to_player(MsgToPlayer::EnterNickname).await;
+                self.naming_players.insert(client_id, player);
+            }
+        }
+    }
+
+    /// Check nickname chosen by client and seat it
+    async fn handle_nickname(
+        &mut self,
+        client_id: ClientId,
+        command: converters::ConversionResult<PlayerCommand<R::Position>>,
+    ) {
+        let nickname = match command {
+            Ok(PlayerCommand::Name(nickname)) => nickname,
+            _ => {
+                let player = self.naming_players.get_mut(&client_id).unwrap();
+                return player.send_msg_to_player(MsgToPlayer::EnterNickname).await;
+            }
+        };
+
+        let used = self
+            .players
+            .values()
+            .filter_map(|player| player.get_nickname())
+            .chain(
+                self.held_seat
+                    .as_ref()
+                    .and_then(|held_seat| held_seat.nickname.as_deref()),
+            );
+
+        match nickname::validate(&nickname, used) {
+            Ok(()) => {
+                let mut player = self.naming_players.remove(&client_id).unwrap();
+
+                log::info!("Client {} uses nickname {}", client_id, nickname);
+                player.set_nickname(nickname);
+                self.seat_player(player).await;
+            }
+            Err(err) => {
+                let player = self.naming_players.get_mut(&client_id).unwrap();
+                player
+                    .send_msg_to_player(MsgToPlayer::InvalidNickname(err))
+                    .await;
+            }
+        }
+    }
+
+    /// Send session token to seated player (if players may reconnect)
+    async fn issue_session_token(&mut self, player: &mut T::NewPlayer) {
+        if self.settings.reconnect_grace.is_some() {
+            let token = generate_session_token();
+
+            player
+                .send_msg_to_player(MsgToPlayer::SessionToken(token.clone()))
+                .await;
+            self.session_tokens.insert(player.get_client_id(), token);
+        }
+    }
+
+    /// Seat player on free place
+    async fn seat_player(&mut self, mut player: T::NewPlayer) {
+        match self.game_stage {
+            GameStage::WaitingForPlayers => {
+                self.issue_session_token(&mut player).await;
+                player.set_role(Role::Player(PlayerId::Circle));
+                player
+                    .send_msg_to_player(MsgToPlayer::WaitingForOtherPlayer)
+                    .await;
+
+                self.players.insert(PlayerId::Circle, player);
+
+                self.game_stage = GameStage::WaitingForPlayer(PlayerId::Cross);
+            }
+            GameStage::WaitingForPlayer(player_id) => {
+                self.issue_session_token(&mut player).await;
+                player.set_role(Role::Player(player_id));
+                self.players.insert(player_id, player);
+
+                self.broadcast_players().await;
+
+                if !self.spectators.is_empty() {
+                    self.broadcast_spectators_count().await;
+                }
+
+                self.start_new_game().await;
+            }
+            _ => {
+                player.set_role(Role::Challenger);
+                self.queue_or_reject(player).await;
+            }
+        }
+    }
+
+    /// Introduce players to each other and to spectators
+    async fn broadcast_players(&mut self) {
+        let circle = self.get_player_info(PlayerId::Circle);
+        let cross = self.get_player_info(PlayerId::Cross);
+
+        for player in self
+            .players
+            .values_mut()
+            .chain(self.spectators.values_mut())
+        {
+            player
+                .send_msg_to_player(MsgToPlayer::PlayersAreReady {
+                    circle: circle.clone(),
+                    cross: cross.clone(),
+                })
+                .await;
+        }
+    }
+
+    /// Put client over limit to queue of challengers or disconnect it
+    async fn queue_or_reject(&mut self, mut player: T::NewPlayer) {
+        let client_id = player.get_client_id();
+
+        if self.settings.queue_challengers {
+            log::info!("Client {} is queued as challenger", client_id);
+            player
+                .send_msg_to_player(MsgToPlayer::QueuedAsChallenger(self.challengers.len() + 1))
+                .await;
+            self.challengers.push_back(player);
+        } else {
+            // Player is disconnected by dropping
+            log::info!("Client {} rejected - server is full", client_id);
+            player.send_msg_to_player(MsgToPlayer::ServerFull).await;
+        }
+    }
+
+    /// Seat waiting challengers on free places
+    async fn seat_challengers(&mut self) {
+        let mut seated = false;
+
+        while self.has_free_seat() {
+            let player_id = match self.game_stage {
+                GameStage::WaitingForPlayer(player_id) => player_id,
+                _ => PlayerId::Circle,
+            };
+
+            let mut player = match self.challengers.pop_front() {
+                Some(x) => x,
+                None => break,
+            };
+
+            log::info!("Challenger {} takes free seat", player.get_client_id());
+            player.set_role(Role::Player(player_id));
+            player.send_msg_to_player(MsgToPlayer::WelcomePlayer).await;
+            self.ask_nickname(player).await;
+            seated = true;
+        }
+
+        if seated {
+            self.broadcast_queue_positions().await;
+        }
+    }
+
+    /// Inform challengers about their position in queue
+    async fn broadcast_queue_positions(&mut self) {
+        for (index, challenger) in self.challengers.iter_mut().enumerate() {
+            challenger
+                .send_msg_to_player(MsgToPlayer::QueuedAsChallenger(index + 1))
+                .await;
+        }
+    }
+
+    /// Add client watching game
+    async fn add_spectator(&mut self, client_id: ClientId, new_player_data: T::NewPlayerData) {
+        let mut spectator =
+            self.player_manager
+                .create_new_player(client_id, Role::Spectator, new_player_data);
+        log::info!("Client {} joined as {}", client_id, spectator.get_role());
+
+        spectator
+            .send_msg_to_player(MsgToPlayer::WelcomeSpectator)
+            .await;
+        spectator
+            .send_msg_to_player(MsgToPlayer::Playboard((self.convert_pb_for_pm)(
+                &self.playboard,
+            )))
+            .await;
+
+        if self.players.len() == 2 {
+            spectator
+                .send_msg_to_player(MsgToPlayer::PlayersAreReady {
+                    circle: self.get_player_info(PlayerId::Circle),
+                    cross: self.get_player_info(PlayerId::Cross),
+                })
+                .await;
+        }
+
+        self.spectators.insert(client_id, spectator);
+        self.broadcast_spectators_count().await;
+    }
+
+    async fn handle_msg(&mut self, client_id: ClientId, msg: T::PlayerMsg) {
+        let command = (self.convert_player_msg_to_command)(msg);
+
+        // Leaderboard is available for all clients
+        if let Ok(PlayerCommand::Leaderboard) = command {
+            return self.send_leaderboard(client_id).await;
+        }
+
+        if let Some(spectator) = self.spectators.get_mut(&client_id) {
+            return spectator
+                .send_msg_to_player(MsgToPlayer::SpectatorsCannotPlay)
+                .await;
+        }
+
+        if let Some(index) = self
+            .challengers
+            .iter()
+            .position(|x| x.get_client_id() == client_id)
+        {
+            return self.challengers[index]
+                .send_msg_to_player(MsgToPlayer::QueuedAsChallenger(index + 1))
+                .await;
+        }
+
+        if self.pending_players.contains_key(&client_id) {
+            return self.handle_session_token(client_id, command).await;
+        }
+
+        if self.naming_players.contains_key(&client_id) {
+            return self.handle_nickname(client_id, command).await;
+        }
+
+        let player_id = match self.find_player_id(client_id) {
+            Some(x) => x,
+            None => return,
+        };
+
+        match self.game_stage {
+            GameStage::PlayerOnMove(player_on_move) | GameStage::SwapDecision(player_on_move) => {
+                self.handle_game_command(player_id, player_on_move, command)
+                    .await
+            }
+            GameStage::RematchVoting => self.handle_rematch_vote(player_id, command).await,
+            _ => (),
+        }
+    }
+
+    async fn handle_game_command(
+        &mut self,
+        player_id: PlayerId,
+        player_on_move: PlayerId,
+        command: converters::ConversionResult<PlayerCommand<R::Position>>,
+    ) {
+        match command {
+            // Commands available for both players
+            Ok(PlayerCommand::Resign) => {
+                self.finish_game(Some(!player_id), ResultReason::Resignation)
+                    .await
+            }
+            Ok(PlayerCommand::Draw) => self.offer_draw(player_id).await,
+            Ok(PlayerCommand::No) if self.draw_offer == Some(!player_id) => {
+                self.decline_draw().await
+            }
+            // Commands available only for player on move
+            _ if player_id != player_on_move => {
+                self.send_to(player_id, MsgToPlayer::YouAreNotOnMove).await
+            }
+            Ok(PlayerCommand::Move(position)) => self.make_move(player_id, position).await,
+            Ok(PlayerCommand::Swap) => match self.game_stage {
+                GameStage::SwapDecision(_) => self.swap_sides(player_id).await,
+                _ => self.send_to(player_id, MsgToPlayer::SwapNotAvailable).await,
+            },
+            Ok(PlayerCommand::Yes)
+            | Ok(PlayerCommand::No)
+            | Ok(PlayerCommand::Resume(_))
+            | Ok(PlayerCommand::Name(_))
+            | Ok(PlayerCommand::Leaderboard)
+            | Err(_) => self.send_to(player_id, MsgToPlayer::InvalidInput).await,
+        }
+    }
+
+    /// Offer draw or accept draw offered by other player
+    async fn offer_draw(&mut self, player_id: PlayerId) {
+        match self.draw_offer {
+            Some(x) if x == !player_id => self.finish_game(None, ResultReason::Agreement).await,
+            Some(_) => (),
+            None => {
+                self.draw_offer = Some(player_id);
+
+                self.send_to(player_id, MsgToPlayer::DrawOfferSent).await;
+                self.send_to(!player_id, MsgToPlayer::DrawOffered).await;
+            }
+        }
+    }
+
+    /// Reject draw offered by other player
+    async fn decline_draw(&mut self) {
+        if let Some(player_id) = self.draw_offer.take() {
+            self.send_to(player_id, MsgToPlayer::DrawOfferDeclined)
+                .await;
+        }
+    }
+
+    async fn handle_rematch_vote(
+        &mut self,
+        player_id: PlayerId,
+        command: converters::ConversionResult<PlayerCommand<R::Position>>,
+    ) {
+        let client_id = self.players[&player_id].get_client_id();
+
+        match command {
+            Ok(PlayerCommand::Yes) => {
+                self.rematch_votes.insert(client_id);
+
+                if self.rematch_votes.len() == self.players.len() {
+                    self.rematch_votes.clear();
+                    self.start_new_game().await;
+                } else {
+                    self.send_to(player_id, MsgToPlayer::WaitingForRematchAnswer)
+                        .await;
+                    self.send_to(!player_id, MsgToPlayer::OtherPlayerWantsRematch)
+                        .await;
+                }
+            }
+            Ok(PlayerCommand::No) => {
+                // Player is disconnected by dropping
+                let mut player = self.players.remove(&player_id).unwrap();
+                player.send_msg_to_player(MsgToPlayer::Goodbye).await;
+                log::info!("Client {} declined rematch", client_id);
+
+                let account = player.get_account().map(|x| x.to_owned());
+                self.release_seat(player_id, client_id, account).await;
+            }
+            _ => self.send_to(player_id, MsgToPlayer::RematchQuestion).await,
+        }
+    }
+
+    async fn make_move(&mut self, player_on_move: PlayerId, position: R::Position) {
+        match self.playboard.new_move(position, player_on_move) {
+            Ok(res) => {
+                self.moves_count += 1;
+                self.clocks.stop();
+
+                // Move of other player rejects draw offer
+                if self.draw_offer == Some(!player_on_move) {
+                    self.decline_draw().await;
+                }
+
+                match res {
+                    playboard::ValidMove::Continue => (),
+                    playboard::ValidMove::Draw => {
+                        self.broadcast_playboard().await;
+                        self.finish_game(None, ResultReason::BoardExhausted).await;
+                        return;
+                    }
+                    playboard::ValidMove::Win => {
+                        self.broadcast_playboard().await;
+                        self.finish_game(Some(player_on_move), ResultReason::LineCompleted)
+                            .await;
+                        return;
+                    }
+                }
+
+                self.broadcast_playboard().await;
+
+                self.game_stage = match self.settings.pie_rule && self.moves_count == 1 {
+                    true => GameStage::SwapDecision(!player_on_move),
+                    false => GameStage::PlayerOnMove(!player_on_move),
+                };
+                self.announce_player_on_move(!player_on_move).await;
+            }
+            Err(err) => match err {
+                playboard::InvalidMove::AlreadyUsed => {
+                    self.send_to(player_on_move, MsgToPlayer::AlreadyTaken)
+                        .await;
+                }
+                playboard::InvalidMove::InvalidRange => {
+                    self.send_to(player_on_move, MsgToPlayer::InvalidInput)
+                        .await
+                }
+            },
+        }
+    }
+
+    /// Save result of finished game and continue by next game
+    async fn finish_game(&mut self, winner: Option<PlayerId>, reason: ResultReason) {
+        self.draw_offer = None;
+        self.clocks.stop();
+
+        match winner {
+            Some(player_id) => {
+                self.send_to(player_id, MsgToPlayer::YouWon(reason)).await;
+                self.send_to(!player_id, MsgToPlayer::YouLose(reason)).await;
+
+                let nickname = self.get_nickname(player_id);
+                for spectator in self.spectators.values_mut() {
+                    spectator
+                        .send_msg_to_player(MsgToPlayer::PlayerWon(
+                            player_id,
+                            nickname.clone(),
+                            reason,
+                        ))
+                        .await;
+                }
+            }
+            None => {
+                for player in self
+                    .players
+                    .values_mut()
+                    .chain(self.spectators.values_mut())
+                {
+                    player.send_msg_to_player(MsgToPlayer::Draw(reason)).await;
+                }
+            }
+        }
+
+        let accounts = (
+            self.get_account(PlayerId::Circle),
+            self.get_account(PlayerId::Cross),
+        );
+        self.update_ratings(accounts, winner).await;
+
+        let (circle, cross) = self.get_client_ids();
+        let winner = winner.map(|player_id| self.players[&player_id].get_client_id());
+
+        match winner {
+            Some(client_id) => self.scoreboard.add_win(client_id),
+            None => self.scoreboard.add_draw(),
+        }
+        self.starting_player.game_finished(winner);
+
+        let match_state = self.scoreboard.get_match_state(circle, cross);
+
+        for player in self.players.values_mut() {
+            let client_id = player.get_client_id();
+            let opponent = match client_id == circle {
+                true => cross,
+                false => circle,
+            };
+
+            player
+                .send_msg_to_player(MsgToPlayer::Score(
+                    self.scoreboard.get_score(client_id, opponent),
+                ))
+                .await;
+
+            match match_state {
+                scoreboard::MatchState::InProgress => (),
+                scoreboard::MatchState::Won(winner) if winner == client_id => {
+                    player.send_msg_to_player(MsgToPlayer::YouWonMatch).await
+                }
+                scoreboard::MatchState::Won(_) => {
+                    player.send_msg_to_player(MsgToPlayer::YouLostMatch).await
+                }
+                scoreboard::MatchState::Drawn => {
+                    player.send_msg_to_player(MsgToPlayer::MatchDrawn).await
+                }
+            }
+        }
+
+        if match_state != scoreboard::MatchState::InProgress {
+            let score = self.scoreboard.get_score(circle, cross);
+            log::info!(
+                "Match between {} ({}) and {} ({}) finished {}:{} ({} draws)",
+                self.get_nickname(PlayerId::Circle),
+                circle,
+                self.get_nickname(PlayerId::Cross),
+                cross,
+                score.wins,
+                score.losses,
+                score.draws
+            );
+
+            self.scoreboard.reset();
+        }
+
+        // Players are asked for rematch after each match (or each game in endless match)
+        match self.settings.match_length.is_none()
+            || match_state != scoreboard::MatchState::InProgress
+        {
+            // Owner of game decides about next game of players
+            true if self.settings.results.is_some() => {
+                self.game_stage = GameStage::RematchVoting;
+            }
+            true => {
+                self.game_stage = GameStage::RematchVoting;
+
+                for player in self.players.values_mut() {
+                    player
+                        .send_msg_to_player(MsgToPlayer::RematchQuestion)
+                        .await;
+                }
+            }
+            false => self.start_new_game().await,
+        }
+
+        // Owner of game may stop room after record, so record is sent after messages to players
+        if let Some(results) = &self.settings.results {
+            let _ = results.send(GameRecord {
+                circle,
+                cross,
+                winner,
+                reason,
+            });
+        }
+    }
+
+    /// Player takes over side of other player (including first move)
+    async fn swap_sides(&mut self, player_id: PlayerId) {
+        self.clocks.stop();
+        self.clocks.swap_sides();
+
+        let mut player = self.players.remove(&player_id).unwrap();
+        let mut other_player = self.players.remove(&!player_id).unwrap();
+
+        player.set_role(Role::Player(!player_id));
+        other_player.set_role(Role::Player(player_id));
+
+        self.players.insert(!player_id, player);
+        self.players.insert(player_id, other_player);
+
+        for player in self
+            .players
+            .values_mut()
+            .chain(self.spectators.values_mut())
+        {
+            player.send_msg_to_player(MsgToPlayer::SidesSwapped).await;
+        }
+
+        // Side on move remains same, only players were exchanged
+        self.game_stage = GameStage::PlayerOnMove(player_id);
+        self.announce_player_on_move(player_id).await;
+    }
+
+    /// Check session token of client joined during interrupted game
+    async fn handle_session_token(
+        &mut self,
+        client_id: ClientId,
+        command: converters::ConversionResult<PlayerCommand<R::Position>>,
+    ) {
+        let is_valid = match (&command, &self.held_seat) {
+            (Ok(PlayerCommand::Resume(token)), Some(held_seat)) => *token == held_seat.token,
+            _ => false,
+        };
+
+        match is_valid {
+            true => self.resume_game(client_id).await,
+            false => {
+                if let Some(player) = self.pending_players.get_mut(&client_id) {
+                    player
+                        .send_msg_to_player(MsgToPlayer::InvalidSessionToken)
+                        .await;
+                }
+            }
+        }
+    }
+
+    /// Hold seat of disconnected player till reconnection
+    async fn hold_seat(
+        &mut self,
+        player_id: PlayerId,
+        client_id: ClientId,
+        nickname: Option<String>,
+        account: Option<String>,
+    ) {
+        let grace = self.settings.reconnect_grace.unwrap();
+
+        log::info!(
+            "Client {} disconnected, waiting {:?} for reconnection",
+            client_id,
+            grace
+        );
+
+        self.clocks.pause();
+
+        self.held_seat = Some(HeldSeat {
+            client_id,
+            nickname,
+            account,
+            token: self.session_tokens.remove(&client_id).unwrap_or_default(),
+            deadline: tokio::time::Instant::now() + grace,
+            interrupted_stage: self.game_stage,
+        });
+        self.game_stage = GameStage::WaitingForReconnection(player_id);
+
+        self.send_to(!player_id, MsgToPlayer::OtherPlayerDisconnected(grace))
+            .await;
+    }
+
+    /// Continue interrupted game with reconnected player
+    async fn resume_game(&mut self, client_id: ClientId) {
+        let player_id = match self.game_stage {
+            GameStage::WaitingForReconnection(x) => x,
+            _ => return,
+        };
+        let held_seat = self.held_seat.take().unwrap();
+        let mut player = self.pending_players.remove(&client_id).unwrap();
+
+        log::info!(
+            "Client {} reconnected as client {}",
+            held_seat.client_id,
+            client_id
+        );
+
+        // Results of previous games belong to new connection
+        self.scoreboard
+            .replace_client(held_seat.client_id, client_id);
+        self.starting_player
+            .replace_client(held_seat.client_id, client_id);
+        self.session_tokens.insert(client_id, held_seat.token);
+
+        if let Some(nickname) = held_seat.nickname {
+            player.set_nickname(nickname);
+        }
+        player.set_role(Role::Player(player_id));
+        player.send_msg_to_player(MsgToPlayer::GameResumed).await;
+        self.players.insert(player_id, player);
+
+        self.send_to(!player_id, MsgToPlayer::OtherPlayerReconnected)
+            .await;
+
+        self.broadcast_playboard().await;
+
+        self.game_stage = held_seat.interrupted_stage;
+        if let GameStage::PlayerOnMove(player_on_move) | GameStage::SwapDecision(player_on_move) =
+            self.game_stage
+        {
+            self.announce_player_on_move(player_on_move).await;
+        }
+
+        // Both players are connected - other waiting clients are not able to join
+        self.seat_pending_players().await;
+    }
+
+    /// Disconnected player did not reconnect in time
+    async fn handle_reconnect_timeout(&mut self) {
+        if let (Some(held_seat), GameStage::WaitingForReconnection(player_id)) =
+            (&self.held_seat, self.game_stage)
+        {
+            log::info!("Client {} did not reconnect in time", held_seat.client_id);
+
+            let client_id = held_seat.client_id;
+            let account = held_seat.account.clone();
+            self.release_seat(player_id, client_id, account).await;
+        }
+    }
+
+    /// Seat clients who waited for reconnection of disconnected player
+    async fn seat_pending_players(&mut self) {
+        let pending_players: Vec<T::NewPlayer> =
+            self.pending_players.drain().map(|(_, x)| x).collect();
+
+        for mut player in pending_players {
+            match self.has_free_seat() {
+                true => self.ask_nickname(player).await,
+                false => {
+                    player.set_role(Role::Challenger);
+                    self.queue_or_reject(player).await;
+                }
+            }
+        }
+    }
+
+    // Client disconnected
+    async fn handle_leave(&mut self, client_id: ClientId) {
+        if self.pending_players.remove(&client_id).is_some() {
+            return;
+        }
+
+        if self.naming_players.remove(&client_id).is_some() {
+            return self.seat_challengers().await;
+        }
+
+        if self.spectators.remove(&client_id).is_some() {
+            return self.broadcast_spectators_count().await;
+        }
+
+        if let Some(index) = self
+            .challengers
+            .iter()
+            .position(|x| x.get_client_id() == client_id)
+        {
+            self.challengers.remove(index);
+            return self.broadcast_queue_positions().await;
+        }
+
+        let id = match self.find_player_id(client_id) {
+            Some(x) => x,
+            None => return,
+        };
+
+        let player = self.players.remove(&id).unwrap();
+
+        // Game in progress is interrupted - player may reconnect
+        let is_game_in_progress = matches!(
+            self.game_stage,
+            GameStage::PlayerOnMove(_) | GameStage::SwapDecision(_)
+        );
+
+        let account = player.get_account().map(|x| x.to_owned());
+
+        if is_game_in_progress && self.settings.reconnect_grace.is_some() {
+            let nickname = player.get_nickname().map(|x| x.to_owned());
+            return self.hold_seat(id, client_id, nickname, account).await;
+        }
+
+        self.release_seat(id, client_id, account).await;
+    }
+
+    /// Free seat of player who left game
+    async fn release_seat(&mut self, id: PlayerId, client_id: ClientId, account: Option<String>) {
+        // Player who left rated game in progress forfeits it
+        if let GameStage::PlayerOnMove(_)
+        | GameStage::SwapDecision(_)
+        | GameStage::WaitingForReconnection(_) = self.game_stage
+        {
+            let accounts = match id {
+                PlayerId::Circle => (account, self.get_account(PlayerId::Cross)),
+                PlayerId::Cross => (self.get_account(PlayerId::Circle), account),
+            };
+            self.update_ratings(accounts, Some(!id)).await;
+        }
+
+        self.session_tokens.remove(&client_id);
+        self.held_seat = None;
+        self.starting_player.reset();
+        self.scoreboard.reset();
+        self.rematch_votes.clear();
+        self.clocks.reset();
+
+        if let GameStage::PlayerOnMove(_)
+        | GameStage::SwapDecision(_)
+        | GameStage::WaitingForReconnection(_) = self.game_stage
+        {
+            self.reset_playboard();
+        }
+
+        if self.players.len() == 1 {
+            let player_ = self.players.values_mut().next().unwrap();
+
+            match self.game_stage {
+                GameStage::RematchVoting => {
+                    player_
+                        .send_msg_to_player(MsgToPlayer::OtherPlayerDeclinedRematch)
+                        .await
+                }
+                _ => {
+                    player_
+                        .send_msg_to_player(MsgToPlayer::OtherPlayerLeave)
+                        .await
+                }
+            }
+
+            player_
+                .send_msg_to_player(MsgToPlayer::WaitingForOtherPlayer)
+                .await;
+
+            self.game_stage = GameStage::WaitingForPlayer(id);
+        } else {
+            self.game_stage = GameStage::WaitingForPlayers;
+        }
+
+        self.seat_pending_players().await;
+        self.seat_challengers().await;
+    }
+
+    /// Time when disconnected player forfeits game
+    fn get_reconnect_deadline(&self) -> Option<tokio::time::Instant> {
+        self.held_seat.as_ref().map(|held_seat| held_seat.deadline)
+    }
+}
+
 /// Run game
 pub async fn run_game<T, R, U, V, W>(
-    mut player_manager: T,
+    player_manager: T,
+    settings: Settings,
     create_pb: U,
-    convert_player_msg_to_coordinates: V,
+    convert_player_msg_to_command: V,
     convert_pb_for_pm: W,
 ) where
-    T: player_manager::PlayerManagerTrait,
+    T: PlayerManagerTrait,
     R: playboard::Playboard,
     U: Fn() -> Result<R, Box<dyn std::error::Error>>,
-    V: Fn(T::PlayerMsg) -> converters::ConversionResult<R::Position>,
-    W: Fn(&R) -> <<T as player_manager::PlayerManagerTrait>::NewPlayer as player_manager::PlayerTrait>::FieldRepresentation,
+    V: Fn(T::PlayerMsg) -> converters::ConversionResult<PlayerCommand<R::Position>>,
+    W: Fn(&R) -> FieldRepresentation<T>,
 {
-    let mut game_stage = GameStage::WaitingForPlayers;
-
-    let mut playboard = match create_pb() {
+    let playboard = match create_pb() {
         Ok(x) => x,
         Err(e) => {
             log::error!(
@@ -74,228 +1288,303 @@ pub async fn run_game<T, R, U, V, W>(
         }
     };
 
-    let mut players: std::collections::HashMap<PlayerId, T::NewPlayer> =
-        std::collections::HashMap::with_capacity(2);
+    let mut game = Game {
+        player_manager,
+        starting_player: starting_policy::StartingPlayerSelector::new(settings.starting_policy),
+        clocks: clock::Clocks::new(settings.time_control),
+        scoreboard: scoreboard::Scoreboard::new(settings.match_length),
+        draw_offer: None,
+        rematch_votes: std::collections::HashSet::with_capacity(2),
+        session_tokens: std::collections::HashMap::with_capacity(2),
+        held_seat: None,
+        pending_players: std::collections::HashMap::new(),
+        naming_players: std::collections::HashMap::new(),
+        settings,
+        create_pb,
+        convert_player_msg_to_command,
+        convert_pb_for_pm,
+        game_stage: GameStage::WaitingForPlayers,
+        playboard,
+        moves_count: 0,
+        players: std::collections::HashMap::with_capacity(2),
+        spectators: std::collections::HashMap::new(),
+        challengers: std::collections::VecDeque::new(),
+    };
+
+    let mut clock_updates = game.settings.clock_updates.map(|period| {
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        interval
+    });
 
     loop {
-        match player_manager.receive_new_message().await {
-            player_manager::MsgFromPlayer::Join(new_player_data) => {
-                match game_stage {
-                    GameStage::WaitingForPlayers => {
-                        let mut player =
-                            player_manager.create_new_player(PlayerId::Circle, new_player_data);
-
-                        player
-                            .send_msg_to_player(player_manager::MsgToPlayer::WelcomePlayer)
-                            .await;
+        let deadline = game.clocks.get_deadline();
+        let reconnect_deadline = game.get_reconnect_deadline();
+        let send_clocks = clock_updates.is_some() && game.clocks.is_running();
 
-                        player
-                            .send_msg_to_player(player_manager::MsgToPlayer::WaitingForOtherPlayer)
-                            .await;
+        tokio::select! {
+            msg = game.player_manager.receive_new_message() => match msg {
+                player_manager::MsgFromPlayer::Join(client_id, new_player_data) => {
+                    game.handle_join(client_id, new_player_data).await
+                }
+                player_manager::MsgFromPlayer::Msg(client_id, msg) => {
+                    game.handle_msg(client_id, msg).await
+                }
+                player_manager::MsgFromPlayer::Leave(client_id) => {
+                    game.handle_leave(client_id).await
+                }
+            },
+            // Player on move ran out of time
+            _ = tokio::time::sleep_until(deadline.unwrap_or_else(tokio::time::Instant::now)),
+                if deadline.is_some() => game.handle_timeout().await,
+            // Disconnected player did not reconnect in time
+            _ = tokio::time::sleep_until(
+                reconnect_deadline.unwrap_or_else(tokio::time::Instant::now)
+            ), if reconnect_deadline.is_some() => game.handle_reconnect_timeout().await,
+            // Periodic update of remaining time
+            _ = async { clock_updates.as_mut().unwrap().tick().await }, if send_clocks => {
+                game.broadcast_clocks().await
+            }
+        }
+    }
+}
 
-                        players.insert(player.get_player_id(), player);
+#[cfg(test)]
+mod test {
+    use super::*;
 
-                        game_stage = GameStage::WaitingForPlayer(PlayerId::Cross);
-                    }
-                    GameStage::WaitingForPlayer(player_id) => {
-                        let mut player =
-                            player_manager.create_new_player(player_id, new_player_data);
-                        player
-                            .send_msg_to_player(player_manager::MsgToPlayer::WelcomePlayer)
-                            .await;
-                        players.insert(player.get_player_id(), player);
+    /// Player reporting messages sent by game as JSON values
+    struct MockPlayer {
+        client_id: ClientId,
+        role: Role,
+        nickname: Option<String>,
+        account: Option<String>,
+        tx: tokio::sync::mpsc::UnboundedSender<(ClientId, serde_json::Value)>,
+    }
 
-                        for player in players.values_mut() {
-                            player
-                                .send_msg_to_player(player_manager::MsgToPlayer::PlayersAreReady)
-                                .await;
+    #[async_trait::async_trait]
+    impl PlayerTrait for MockPlayer {
+        type FieldRepresentation = converters::Board;
 
-                            let x = convert_pb_for_pm(&playboard);
+        async fn send_msg_to_player(&mut self, msg: MsgToPlayer<Self::FieldRepresentation>) {
+            let _ = self
+                .tx
+                .send((self.client_id, serde_json::to_value(&msg).unwrap()));
+        }
 
-                            player
-                                .send_msg_to_player(player_manager::MsgToPlayer::Playboard(x))
-                                .await;
-                        }
+        fn get_role(&self) -> Role {
+            self.role
+        }
 
-                        game_stage = GameStage::PlayerOnMove(!player_id);
+        fn set_role(&mut self, role: Role) {
+            self.role = role;
+        }
 
-                        players
-                            .get_mut(&!player_id)
-                            .unwrap()
-                            .send_msg_to_player(player_manager::MsgToPlayer::YourAreOnMove)
-                            .await;
+        fn get_nickname(&self) -> Option<&str> {
+            self.nickname.as_deref()
+        }
 
-                        players
-                            .get_mut(&player_id)
-                            .unwrap()
-                            .send_msg_to_player(player_manager::MsgToPlayer::OtherPlayerIsOnMove)
-                            .await;
-                    }
-                    _ => {
-                        // TODO - close connection
-                        println!("Both players are connected")
-                    }
-                }
+        fn set_nickname(&mut self, nickname: String) {
+            self.nickname = Some(nickname);
+        }
+
+        fn get_account(&self) -> Option<&str> {
+            self.account.as_deref()
+        }
+
+        fn get_client_id(&self) -> ClientId {
+            self.client_id
+        }
+    }
+
+    /// Player manager of clients controlled by test - new player data is name of account
+    struct MockPlayerManager {
+        rx: tokio::sync::mpsc::UnboundedReceiver<
+            player_manager::MsgFromPlayer<Option<String>, String>,
+        >,
+        tx: tokio::sync::mpsc::UnboundedSender<(ClientId, serde_json::Value)>,
+    }
+
+    #[async_trait::async_trait]
+    impl PlayerManagerTrait for MockPlayerManager {
+        type NewPlayerData = Option<String>;
+        type NewPlayer = MockPlayer;
+        type PlayerMsg = String;
+
+        fn create_new_player(
+            &self,
+            client_id: ClientId,
+            role: Role,
+            player_data: Self::NewPlayerData,
+        ) -> Self::NewPlayer {
+            MockPlayer {
+                client_id,
+                role,
+                nickname: None,
+                account: player_data,
+                tx: self.tx.clone(),
             }
-            player_manager::MsgFromPlayer::Msg(player_id, msg) => {
-                if let GameStage::PlayerOnMove(player_on_move) = game_stage {
-                    match player_id == player_on_move {
-                        true => {
-                            let player_ = players.get_mut(&player_on_move).unwrap();
-
-                            match convert_player_msg_to_coordinates(msg) {
-                                Ok(position) => match playboard.new_move(position, player_id) {
-                                    Ok(res) => {
-                                        match res {
-                                            playboard::ValidMove::Continue => (),
-                                            playboard::ValidMove::Draw => {
-                                                for player in players.values_mut() {
-                                                    player
-                                                        .send_msg_to_player(
-                                                            player_manager::MsgToPlayer::Playboard(
-                                                                convert_pb_for_pm(&playboard),
-                                                            ),
-                                                        )
-                                                        .await;
-
-                                                    player
-                                                        .send_msg_to_player(
-                                                            player_manager::MsgToPlayer::Draw,
-                                                        )
-                                                        .await;
-                                                }
-                                                playboard = create_pb().unwrap();
-                                            }
-                                            playboard::ValidMove::Win => {
-                                                let x = convert_pb_for_pm(&playboard);
-                                                for player in players.values_mut() {
-                                                    player
-                                                        .send_msg_to_player(
-                                                            player_manager::MsgToPlayer::Playboard(
-                                                                convert_pb_for_pm(&playboard),
-                                                            ),
-                                                        )
-                                                        .await;
-                                                }
-
-                                                players
-                                                    .get_mut(&player_on_move)
-                                                    .unwrap()
-                                                    .send_msg_to_player(
-                                                        player_manager::MsgToPlayer::YouWon,
-                                                    )
-                                                    .await;
-
-                                                players
-                                                    .get_mut(&!player_on_move)
-                                                    .unwrap()
-                                                    .send_msg_to_player(
-                                                        player_manager::MsgToPlayer::YouLose,
-                                                    )
-                                                    .await;
-
-                                                playboard = create_pb().unwrap();
-                                            }
-                                        }
-                                        let x = convert_pb_for_pm(&playboard);
-
-                                        players
-                                            .get_mut(&!player_on_move)
-                                            .unwrap()
-                                            .send_msg_to_player(
-                                                player_manager::MsgToPlayer::Playboard(
-                                                    convert_pb_for_pm(&playboard),
-                                                ),
-                                            )
-                                            .await;
-                                        players
-                                            .get_mut(&player_on_move)
-                                            .unwrap()
-                                            .send_msg_to_player(
-                                                player_manager::MsgToPlayer::Playboard(
-                                                    convert_pb_for_pm(&playboard),
-                                                ),
-                                            )
-                                            .await;
-
-                                        players
-                                            .get_mut(&!player_on_move)
-                                            .unwrap()
-                                            .send_msg_to_player(
-                                                player_manager::MsgToPlayer::YourAreOnMove,
-                                            )
-                                            .await;
-                                        players
-                                            .get_mut(&player_on_move)
-                                            .unwrap()
-                                            .send_msg_to_player(
-                                                player_manager::MsgToPlayer::OtherPlayerIsOnMove,
-                                            )
-                                            .await;
-
-                                        game_stage = GameStage::PlayerOnMove(!player_on_move);
-                                    }
-                                    Err(err) => match err {
-                                        playboard::InvalidMove::AlreadyUsed => {
-                                            player_
-                                                .send_msg_to_player(
-                                                    player_manager::MsgToPlayer::AlreadyTaken,
-                                                )
-                                                .await;
-                                        }
-                                        playboard::InvalidMove::InvalidRange => {
-                                            player_
-                                                .send_msg_to_player(
-                                                    player_manager::MsgToPlayer::InvalidInput,
-                                                )
-                                                .await
-                                        }
-                                    },
-                                },
-                                Err(_) => {
-                                    player_
-                                        .send_msg_to_player(
-                                            player_manager::MsgToPlayer::InvalidInput,
-                                        )
-                                        .await
-                                }
-                            };
-                        }
-                        false => {
-                            players
-                                .get_mut(&player_id)
-                                .unwrap()
-                                .send_msg_to_player(player_manager::MsgToPlayer::YouAreNotOnMove)
-                                .await
-                        }
-                    }
-                };
+        }
+
+        async fn receive_new_message(
+            &mut self,
+        ) -> player_manager::MsgFromPlayer<Self::NewPlayerData, Self::PlayerMsg> {
+            match self.rx.recv().await {
+                Some(msg) => msg,
+                None => std::future::pending().await,
             }
+        }
+    }
 
-            // Client disconnected
-            player_manager::MsgFromPlayer::Leave(id) => {
-                players.remove(&id);
+    /// Game running in background with clients controlled by test
+    struct Table {
+        tx: tokio::sync::mpsc::UnboundedSender<
+            player_manager::MsgFromPlayer<Option<String>, String>,
+        >,
+        rx: tokio::sync::mpsc::UnboundedReceiver<(ClientId, serde_json::Value)>,
+    }
 
-                if let GameStage::PlayerOnMove(_) = game_stage {
-                    playboard = create_pb().unwrap();
-                }
+    impl Table {
+        fn new(settings: Settings) -> Self {
+            let (tx, rx_game) = tokio::sync::mpsc::unbounded_channel();
+            let (tx_game, rx) = tokio::sync::mpsc::unbounded_channel();
 
-                if players.len() == 1 {
-                    let player_ = players.values_mut().next().unwrap();
+            tokio::spawn(run_game(
+                MockPlayerManager {
+                    rx: rx_game,
+                    tx: tx_game,
+                },
+                settings,
+                || playboard::pb_n_n::Playboard::new(3),
+                converters::pm_tcp_msg_to_command,
+                converters::pb_n_n_to_board,
+            ));
 
-                    player_
-                        .send_msg_to_player(player_manager::MsgToPlayer::OtherPlayerLeave)
-                        .await;
+            Table { tx, rx }
+        }
 
-                    player_
-                        .send_msg_to_player(player_manager::MsgToPlayer::WaitingForOtherPlayer)
-                        .await;
+        fn join(&self, client_id: usize, account: Option<&str>) {
+            let _ = self.tx.send(player_manager::MsgFromPlayer::Join(
+                ClientId(client_id),
+                account.map(|x| x.to_owned()),
+            ));
+        }
 
-                    game_stage = GameStage::WaitingForPlayer(id);
-                } else {
-                    game_stage = GameStage::WaitingForPlayers;
+        fn leave(&self, client_id: usize) {
+            let _ = self
+                .tx
+                .send(player_manager::MsgFromPlayer::Leave(ClientId(client_id)));
+        }
+
+        fn send(&self, client_id: usize, msg: &str) {
+            let _ = self.tx.send(player_manager::MsgFromPlayer::Msg(
+                ClientId(client_id),
+                msg.to_owned(),
+            ));
+        }
+
+        /// Wait for message of given type sent to client (other messages are skipped)
+        async fn expect(&mut self, client_id: usize, msg_type: &str) -> serde_json::Value {
+            let wait = async {
+                loop {
+                    let (id, msg) = self.rx.recv().await.unwrap();
+                    if id == ClientId(client_id) && msg["type"] == msg_type {
+                        return msg;
+                    }
                 }
+            };
+
+            match tokio::time::timeout(std::time::Duration::from_secs(1), wait).await {
+                Ok(msg) => msg,
+                Err(_) => panic!("Client #{} did not receive {}", client_id, msg_type),
             }
+        }
+
+        /// Seat two clients with nicknames - client 0 plays for circle and starts
+        async fn seat_players(&mut self) {
+            self.join(0, None);
+            self.join(1, None);
+            self.expect(1, "enter_nickname").await;
+            self.send(0, "name alice");
+            self.send(1, "name bob");
+            self.expect(0, "your_are_on_move").await;
+        }
+    }
+
+    #[tokio::test]
+    async fn swap_of_sides() {
+        let mut table = Table::new(Settings {
+            pie_rule: true,
+            ..Default::default()
+        });
+        table.seat_players().await;
+
+        table.send(0, "1-1");
+        table.expect(1, "swap_available").await;
+        table.expect(1, "your_are_on_move").await;
+
+        // Client 1 takes over circle with its first move - client 0 continues as cross
+        table.send(1, "swap");
+        table.expect(0, "sides_swapped").await;
+        table.expect(0, "your_are_on_move").await;
+
+        table.send(0, "2-2");
+        let playboard = table.expect(1, "playboard").await;
+        assert_eq!(
+            playboard["data"],
+            serde_json::json!([["o", null, null], [null, "x", null], [null, null, null]])
+        );
+        table.expect(1, "your_are_on_move").await;
+
+        // Swap is possible only once after first move
+        table.send(1, "swap");
+        table.expect(1, "swap_not_available").await;
+    }
+
+    #[tokio::test]
+    async fn swap_without_pie_rule() {
+        let mut table = Table::new(Settings::default());
+        table.seat_players().await;
+
+        table.send(0, "1-1");
+        table.expect(1, "your_are_on_move").await;
+
+        table.send(1, "swap");
+        table.expect(1, "swap_not_available").await;
+    }
+
+    #[tokio::test]
+    async fn disconnected_player_forfeits_rated_game() {
+        let path = std::env::temp_dir().join(format!("game-ratings-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let pool = rating::Pool {
+            size: 3,
+            win_length: 3,
         };
+        let store = std::sync::Arc::new(rating::RatingStore::open(&path).unwrap());
+
+        let mut table = Table::new(Settings {
+            reconnect_grace: Some(std::time::Duration::from_millis(10)),
+            ratings: Some(rating::RatedPool {
+                store: store.clone(),
+                pool,
+            }),
+            ..Default::default()
+        });
+        table.join(0, Some("alice"));
+        table.join(1, Some("bob"));
+        table.expect(0, "your_are_on_move").await;
+
+        // Client 0 does not reconnect in time
+        table.leave(0);
+        table.expect(1, "other_player_disconnected").await;
+        let change = table.expect(1, "rating_changed").await;
+        assert_eq!(change["data"]["change"], 16);
+        table.expect(1, "other_player_leave").await;
+
+        assert_eq!(store.get(&pool, "alice"), 1484);
+        assert_eq!(store.get(&pool, "bob"), 1516);
+
+        let _ = std::fs::remove_file(&path);
     }
 }
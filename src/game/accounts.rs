@@ -0,0 +1,202 @@
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, SaltString};
+use argon2::PasswordVerifier;
+use std::io::Write;
+
+/// Minimal number of characters in password
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Enum for failed operation with account
+#[derive(Debug)]
+pub enum AccountError {
+    /// Name of account is not valid nickname (or it is already used)
+    InvalidName(super::nickname::NicknameError),
+    /// Password is too short
+    WeakPassword,
+    /// Name or password is not correct
+    InvalidCredentials,
+    /// Account store is not accessible
+    Storage(std::io::Error),
+}
+
+impl std::fmt::Display for AccountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidName(err) => write!(f, "invalid name of account ({:?})", err),
+            Self::WeakPassword => write!(f, "password is too short"),
+            Self::InvalidCredentials => write!(f, "invalid name or password"),
+            Self::Storage(err) => write!(f, "account store error ({})", err),
+        }
+    }
+}
+
+/// Accounts of players saved in local file
+///
+/// Each line of file contains name of account and PHC string of argon2 hash of password
+/// separated by colon.
+pub struct AccountStore {
+    path: std::path::PathBuf,
+    accounts: tokio::sync::Mutex<std::collections::HashMap<String, String>>,
+}
+
+/// Compute argon2 hash of password in PHC format
+fn hash_password(password: &str) -> Result<String, AccountError> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    argon2::Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|_| AccountError::InvalidCredentials)
+}
+
+/// Check password against hash in PHC format
+fn verify_password(password: &str, hash: &str) -> bool {
+    match PasswordHash::new(hash) {
+        Ok(hash) => argon2::Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+impl AccountStore {
+    /// Load accounts from file (file is created by first registration)
+    pub fn open<P>(path: P) -> std::io::Result<AccountStore>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(x) => x,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        let accounts = content
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, hash)| (name.to_owned(), hash.to_owned()))
+            .collect();
+
+        Ok(AccountStore {
+            path: path.as_ref().to_owned(),
+            accounts: tokio::sync::Mutex::new(accounts),
+        })
+    }
+
+    /// Create new account
+    pub async fn register(&self, name: &str, password: &str) -> Result<(), AccountError> {
+        Self::check_new_name(&*self.accounts.lock().await, name)?;
+
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AccountError::WeakPassword);
+        }
+
+        // Memory-hard hashing would block other tasks
+        let password = password.to_owned();
+        let hash = tokio::task::spawn_blocking(move || hash_password(&password))
+            .await
+            .map_err(|err| AccountError::Storage(err.into()))??;
+
+        // Name may be registered by other client during hashing, so it is checked again
+        // under the same guard as the insertion
+        let mut accounts = self.accounts.lock().await;
+        Self::check_new_name(&accounts, name)?;
+
+        let path = self.path.clone();
+        let line = format!("{}:{}", name, hash);
+        tokio::task::spawn_blocking(move || {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| writeln!(file, "{}", line))
+        })
+        .await
+        .map_err(|err| AccountError::Storage(err.into()))?
+        .map_err(AccountError::Storage)?;
+
+        accounts.insert(name.to_owned(), hash);
+
+        Ok(())
+    }
+
+    /// Check name and password of existing account
+    pub async fn login(&self, name: &str, password: &str) -> Result<(), AccountError> {
+        let hash = self.accounts.lock().await.get(name).cloned();
+
+        // Password is hashed even for unknown account, so its existence is not revealed by time
+        let password = password.to_owned();
+        let is_valid = tokio::task::spawn_blocking(move || match hash {
+            Some(hash) => verify_password(&password, &hash),
+            None => {
+                let _ = hash_password(&password);
+                false
+            }
+        })
+        .await
+        .unwrap_or(false);
+
+        match is_valid {
+            true => Ok(()),
+            false => Err(AccountError::InvalidCredentials),
+        }
+    }
+
+    fn check_new_name(
+        accounts: &std::collections::HashMap<String, String>,
+        name: &str,
+    ) -> Result<(), AccountError> {
+        super::nickname::validate(name, accounts.keys().map(|x| x.as_str()))
+            .map_err(AccountError::InvalidName)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn register_and_login() {
+        let path = std::env::temp_dir().join(format!("accounts-test-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = AccountStore::open(&path).unwrap();
+        assert!(store.register("alice", "password123").await.is_ok());
+        assert!(matches!(
+            store.register("Alice", "password123").await,
+            Err(AccountError::InvalidName(
+                crate::game::nickname::NicknameError::AlreadyUsed
+            ))
+        ));
+        assert!(matches!(
+            store.register("bob", "short").await,
+            Err(AccountError::WeakPassword)
+        ));
+
+        // Accounts are loaded again from file
+        let store = AccountStore::open(&path).unwrap();
+        assert!(store.login("alice", "password123").await.is_ok());
+        assert!(store.login("alice", "wrong password").await.is_err());
+        assert!(store.login("bob", "password123").await.is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn concurrent_registration_of_same_name() {
+        let path = std::env::temp_dir().join(format!("accounts-race-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = AccountStore::open(&path).unwrap();
+        let (first, second) = tokio::join!(
+            store.register("carol", "password123"),
+            store.register("carol", "password456")
+        );
+        assert!(first.is_ok() != second.is_ok());
+
+        // Only one account is saved
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 1);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,208 @@
+use super::PlayerId;
+
+/// Enum for time limits of players
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TimeControl {
+    /// Players can think without any limit
+    #[default]
+    Unlimited,
+    /// Each move has to be done in fixed time
+    PerMove(std::time::Duration),
+    /// Each player has total time for game, which is extended by increment after each move
+    Clock {
+        initial: std::time::Duration,
+        increment: std::time::Duration,
+    },
+}
+
+/// Remaining time of players
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct ClockState {
+    #[serde(serialize_with = "serialize_seconds")]
+    pub circle: std::time::Duration,
+    #[serde(serialize_with = "serialize_seconds")]
+    pub cross: std::time::Duration,
+}
+
+/// Serialize duration as number of whole seconds
+pub fn serialize_seconds<S>(
+    duration: &std::time::Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_u64(duration.as_secs())
+}
+
+impl ClockState {
+    /// Get remaining time of player
+    pub fn get(&self, player_id: PlayerId) -> std::time::Duration {
+        match player_id {
+            PlayerId::Circle => self.circle,
+            PlayerId::Cross => self.cross,
+        }
+    }
+}
+
+/// Clocks of both players
+pub struct Clocks {
+    time_control: TimeControl,
+    /// Remaining time of players when their clock is stopped
+    remaining: ClockState,
+    /// Player whose clock is running and time when it was started
+    running: Option<(PlayerId, tokio::time::Instant)>,
+}
+
+impl Clocks {
+    pub fn new(time_control: TimeControl) -> Self {
+        let mut clocks = Clocks {
+            time_control,
+            remaining: ClockState {
+                circle: std::time::Duration::ZERO,
+                cross: std::time::Duration::ZERO,
+            },
+            running: None,
+        };
+        clocks.reset();
+
+        clocks
+    }
+
+    /// Time available for move (or game) at beginning of game
+    fn initial_time(&self) -> std::time::Duration {
+        match self.time_control {
+            TimeControl::Unlimited => std::time::Duration::ZERO,
+            TimeControl::PerMove(x) => x,
+            TimeControl::Clock { initial, .. } => initial,
+        }
+    }
+
+    /// Check if players are limited by time
+    pub fn is_enabled(&self) -> bool {
+        self.time_control != TimeControl::Unlimited
+    }
+
+    /// Check if clock of any player is running
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Set clocks for new game
+    pub fn reset(&mut self) {
+        let initial = self.initial_time();
+
+        self.remaining = ClockState {
+            circle: initial,
+            cross: initial,
+        };
+        self.running = None;
+    }
+
+    /// Start clock of player on move
+    pub fn start(&mut self, player_id: PlayerId) {
+        if self.is_enabled() {
+            self.running = Some((player_id, tokio::time::Instant::now()));
+        }
+    }
+
+    /// Stop running clock after move was made
+    pub fn stop(&mut self) {
+        let (player_id, started) = match self.running.take() {
+            Some(x) => x,
+            None => return,
+        };
+
+        let remaining = match self.time_control {
+            TimeControl::Unlimited => return,
+            TimeControl::PerMove(x) => x,
+            TimeControl::Clock { increment, .. } => {
+                self.remaining
+                    .get(player_id)
+                    .saturating_sub(started.elapsed())
+                    + increment
+            }
+        };
+
+        match player_id {
+            PlayerId::Circle => self.remaining.circle = remaining,
+            PlayerId::Cross => self.remaining.cross = remaining,
+        }
+    }
+
+    /// Stop running clock without finishing move (e.g. player disconnected)
+    pub fn pause(&mut self) {
+        self.remaining = self.get_state();
+        self.running = None;
+    }
+
+    /// Exchange clocks of players after swap of sides
+    pub fn swap_sides(&mut self) {
+        self.remaining = ClockState {
+            circle: self.remaining.cross,
+            cross: self.remaining.circle,
+        };
+    }
+
+    /// Time when player on move runs out of time
+    pub fn get_deadline(&self) -> Option<tokio::time::Instant> {
+        self.running
+            .map(|(player_id, started)| started + self.remaining.get(player_id))
+    }
+
+    /// Get current remaining time of players
+    pub fn get_state(&self) -> ClockState {
+        let mut state = self.remaining;
+
+        if let Some((player_id, started)) = self.running {
+            let remaining = self
+                .remaining
+                .get(player_id)
+                .saturating_sub(started.elapsed());
+
+            match player_id {
+                PlayerId::Circle => state.circle = remaining,
+                PlayerId::Cross => state.cross = remaining,
+            }
+        }
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MINUTE: std::time::Duration = std::time::Duration::from_secs(60);
+    const SECOND: std::time::Duration = std::time::Duration::from_secs(1);
+
+    #[test]
+    fn unlimited_clock_is_never_running() {
+        let mut clocks = Clocks::new(TimeControl::Unlimited);
+
+        clocks.start(PlayerId::Circle);
+
+        assert!(!clocks.is_running());
+        assert_eq!(clocks.get_deadline(), None);
+    }
+
+    #[test]
+    fn clock_with_increment() {
+        let mut clocks = Clocks::new(TimeControl::Clock {
+            initial: MINUTE,
+            increment: SECOND,
+        });
+
+        clocks.start(PlayerId::Circle);
+        assert!(clocks.get_deadline().is_some());
+        clocks.stop();
+
+        let state = clocks.get_state();
+        assert!(state.circle > MINUTE && state.circle <= MINUTE + SECOND);
+        assert_eq!(state.cross, MINUTE);
+
+        clocks.swap_sides();
+        assert_eq!(clocks.get_state().circle, MINUTE);
+    }
+}
@@ -24,19 +24,16 @@ where
 {
     let mut inputs = input.as_ref().split('-');
 
-    let mut x = 0_usize;
-    let mut y = 0_usize;
-
-    match inputs.next() {
-        Some(value) => x = get_position_from_string(value)?,
+    let x = match inputs.next() {
+        Some(value) => get_position_from_string(value)?,
         None => return Err(ConversionError {}),
-    }
+    };
 
-    match inputs.next() {
-        Some(value) => y = get_position_from_string(value)?,
+    let y = match inputs.next() {
+        Some(value) => get_position_from_string(value)?,
 
         None => return Err(ConversionError {}),
-    }
+    };
 
     match inputs.next() {
         Some(_) => Err(ConversionError {}),
@@ -44,6 +41,189 @@ where
     }
 }
 
+/// Text command or JSON command of structured protocol
+pub fn pm_tcp_msg_to_command<T>(input: T) -> ConversionResult<super::PlayerCommand<(usize, usize)>>
+where
+    T: std::convert::AsRef<str>,
+{
+    let input = input.as_ref().trim();
+
+    if input.starts_with('{') {
+        return pm_json_msg_to_command(input);
+    }
+
+    if let Some(token) = input.strip_prefix("resume ") {
+        return Ok(super::PlayerCommand::Resume(token.trim().to_owned()));
+    }
+
+    if let Some(nickname) = input.strip_prefix("name ") {
+        return Ok(super::PlayerCommand::Name(nickname.trim().to_owned()));
+    }
+
+    match input {
+        "swap" => Ok(super::PlayerCommand::Swap),
+        "yes" | "y" => Ok(super::PlayerCommand::Yes),
+        "no" | "n" => Ok(super::PlayerCommand::No),
+        "resign" => Ok(super::PlayerCommand::Resign),
+        "draw" => Ok(super::PlayerCommand::Draw),
+        "leaderboard" => Ok(super::PlayerCommand::Leaderboard),
+        other => pm_tcp_msg_to_x_y(other).map(super::PlayerCommand::Move),
+    }
+}
+
+/// Lobby command of structured protocol - JSON object with "type" field
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonLobbyCommand {
+    Create {
+        size: usize,
+        win_length: Option<usize>,
+    },
+    List,
+    Join {
+        room: usize,
+    },
+    Play {
+        size: usize,
+        rating: Option<u32>,
+    },
+    Cancel,
+    Resume {
+        token: String,
+    },
+}
+
+pub fn pm_json_msg_to_lobby_command<T>(input: T) -> ConversionResult<super::lobby::LobbyCommand>
+where
+    T: std::convert::AsRef<str>,
+{
+    let command = serde_json::from_str(input.as_ref()).map_err(|_| ConversionError)?;
+
+    Ok(match command {
+        JsonLobbyCommand::Create { size, win_length } => {
+            super::lobby::LobbyCommand::Create(super::lobby::RoomOptions {
+                size,
+                win_length: win_length.unwrap_or(size),
+            })
+        }
+        JsonLobbyCommand::List => super::lobby::LobbyCommand::List,
+        JsonLobbyCommand::Join { room } => {
+            super::lobby::LobbyCommand::Join(super::lobby::RoomId(room))
+        }
+        JsonLobbyCommand::Play { size, rating } => {
+            super::lobby::LobbyCommand::Play(super::lobby::QueueRequest { size, rating })
+        }
+        JsonLobbyCommand::Cancel => super::lobby::LobbyCommand::Cancel,
+        JsonLobbyCommand::Resume { token } => super::lobby::LobbyCommand::Resume(token),
+    })
+}
+
+/// Text lobby command or JSON lobby command of structured protocol
+pub fn pm_tcp_msg_to_lobby_command<T>(input: T) -> ConversionResult<super::lobby::LobbyCommand>
+where
+    T: std::convert::AsRef<str>,
+{
+    if input.as_ref().trim().starts_with('{') {
+        return pm_json_msg_to_lobby_command(input.as_ref().trim());
+    }
+
+    let words: Vec<&str> = input.as_ref().split_whitespace().collect();
+
+    match words.as_slice() {
+        ["list"] => Ok(super::lobby::LobbyCommand::List),
+        ["cancel"] => Ok(super::lobby::LobbyCommand::Cancel),
+        ["resume", token] => Ok(super::lobby::LobbyCommand::Resume(token.to_string())),
+        ["play", size] => Ok(super::lobby::LobbyCommand::Play(
+            super::lobby::QueueRequest {
+                size: convert_input_to_usize(size)?,
+                rating: None,
+            },
+        )),
+        ["play", size, rating] => Ok(super::lobby::LobbyCommand::Play(
+            super::lobby::QueueRequest {
+                size: convert_input_to_usize(size)?,
+                rating: Some(rating.trim().parse().map_err(|_| ConversionError)?),
+            },
+        )),
+        ["join", room_id] => Ok(super::lobby::LobbyCommand::Join(super::lobby::RoomId(
+            convert_input_to_usize(room_id)?,
+        ))),
+        ["create", size] => {
+            let size = convert_input_to_usize(size)?;
+
+            Ok(super::lobby::LobbyCommand::Create(
+                super::lobby::RoomOptions {
+                    size,
+                    win_length: size,
+                },
+            ))
+        }
+        ["create", size, win_length] => Ok(super::lobby::LobbyCommand::Create(
+            super::lobby::RoomOptions {
+                size: convert_input_to_usize(size)?,
+                win_length: convert_input_to_usize(win_length)?,
+            },
+        )),
+        _ => Err(ConversionError),
+    }
+}
+
+/// Command of structured protocol - JSON object with "type" field
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonCommand {
+    /// Position is indexed from 1 as in text protocol
+    Move {
+        row: usize,
+        column: usize,
+    },
+    Swap,
+    Yes,
+    No,
+    Resign,
+    Draw,
+    Resume {
+        token: String,
+    },
+    Name {
+        nickname: String,
+    },
+    Leaderboard,
+}
+
+pub fn pm_json_msg_to_command<T>(input: T) -> ConversionResult<super::PlayerCommand<(usize, usize)>>
+where
+    T: std::convert::AsRef<str>,
+{
+    let command = serde_json::from_str(input.as_ref()).map_err(|_| ConversionError)?;
+
+    Ok(match command {
+        JsonCommand::Move { row, column } => super::PlayerCommand::Move((
+            row.checked_sub(1).ok_or(ConversionError)?,
+            column.checked_sub(1).ok_or(ConversionError)?,
+        )),
+        JsonCommand::Swap => super::PlayerCommand::Swap,
+        JsonCommand::Yes => super::PlayerCommand::Yes,
+        JsonCommand::No => super::PlayerCommand::No,
+        JsonCommand::Resign => super::PlayerCommand::Resign,
+        JsonCommand::Draw => super::PlayerCommand::Draw,
+        JsonCommand::Resume { token } => super::PlayerCommand::Resume(token),
+        JsonCommand::Name { nickname } => super::PlayerCommand::Name(nickname),
+        JsonCommand::Leaderboard => super::PlayerCommand::Leaderboard,
+    })
+}
+
+/// Rows of playboard with symbols of players (None for free field)
+pub type Board = Vec<Vec<Option<super::PlayerId>>>;
+
+pub fn pb_n_n_to_board(playboard: &playboard::pb_n_n::Playboard) -> Board {
+    playboard
+        .fields
+        .chunks(playboard.edge_size)
+        .map(|row| row.iter().map(|x| x.field).collect())
+        .collect()
+}
+
 pub fn pb_n_n_to_string(playboard: &playboard::pb_n_n::Playboard) -> String {
     let mut rows: Vec<String> = Vec::with_capacity(playboard.edge_size + 2);
 
@@ -86,6 +266,100 @@ pub fn pb_n_n_to_string(playboard: &playboard::pb_n_n::Playboard) -> String {
     rows.join(&rows_separator)
 }
 
+/// Playboard for clients of both text and structured protocol
+#[derive(Debug, PartialEq, Clone)]
+pub struct RenderedPlayboard {
+    /// Playboard drawn by pb_n_n_to_string
+    pub text: String,
+    /// Playboard sent in JSON messages
+    pub board: Board,
+}
+
+/// Only board is part of JSON messages
+impl serde::Serialize for RenderedPlayboard {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.board.serialize(serializer)
+    }
+}
+
+/// Clients of structured protocol need only board
+impl From<RenderedPlayboard> for Board {
+    fn from(playboard: RenderedPlayboard) -> Self {
+        playboard.board
+    }
+}
+
+pub fn pb_n_n_to_rendered(playboard: &playboard::pb_n_n::Playboard) -> RenderedPlayboard {
+    RenderedPlayboard {
+        text: pb_n_n_to_string(playboard),
+        board: pb_n_n_to_board(playboard),
+    }
+}
+
+/// Render board as text table with indexes of rows and columns drawn by unicode characters
+/// and/or with colored symbols of players (pb_n_n_to_string draws plain table)
+pub fn board_to_string(board: &Board, unicode: bool, colors: bool) -> String {
+    let (horizontal, vertical) = match unicode {
+        true => ("\u{2500}", "\u{2502}"),
+        false => ("-", "|"),
+    };
+    let crossing = match unicode {
+        true => "\u{253c}",
+        false => "|",
+    };
+
+    let edge_size = board.len();
+    let mut rows: Vec<String> = Vec::with_capacity(edge_size + 2);
+
+    // Rows separator
+    let rows_separator: String = "\r\n".to_owned()
+        + &(0..edge_size + 2)
+            .map(|_| horizontal.to_owned())
+            .collect::<Vec<String>>()
+            .join(crossing)
+        + "\r\n";
+
+    // Create "header" for column indexes
+    let column_indexes = format!(" {}", vertical)
+        + &(0..edge_size)
+            .map(|x| format!("{}", x + 1))
+            .collect::<Vec<String>>()
+            .join(vertical)
+        + &format!("{} ", vertical);
+
+    rows.push(column_indexes.clone());
+
+    // Push playboard field
+    board.iter().enumerate().for_each(|(index, row)| {
+        let middle = row
+            .iter()
+            .map(|field| match (field, colors) {
+                (Some(super::PlayerId::Circle), true) => "\x1b[34mo\x1b[0m".to_owned(),
+                (Some(super::PlayerId::Cross), true) => "\x1b[31mx\x1b[0m".to_owned(),
+                (Some(player_id), false) => player_id.to_string(),
+                (None, _) => " ".to_owned(),
+            })
+            .collect::<Vec<String>>()
+            .join(vertical);
+
+        rows.push(format!(
+            "{}{}{}{}{}",
+            index + 1,
+            vertical,
+            middle,
+            vertical,
+            index + 1
+        ))
+    });
+
+    rows.push(column_indexes);
+
+    rows.join(&rows_separator)
+}
+
 #[cfg(test)]
 mod test {
 
@@ -101,4 +375,152 @@ mod test {
             println!("{}", pb_n_n_to_string(&playboard));
         }
     }
+
+    mod test_pb_n_n_to_rendered {
+        use super::super::*;
+
+        #[test]
+        fn json_contains_only_board() {
+            let playboard = playboard::pb_n_n::Playboard::new(3).unwrap();
+            let rendered = pb_n_n_to_rendered(&playboard);
+
+            assert_eq!(
+                board_to_string(&rendered.board, false, false),
+                rendered.text
+            );
+            assert_eq!(
+                serde_json::to_value(&rendered).unwrap(),
+                serde_json::json!([[null, null, null], [null, null, null], [null, null, null]])
+            );
+        }
+    }
+
+    mod test_pm_tcp_msg_to_lobby_command {
+        use super::super::*;
+
+        #[test]
+        fn create_command() {
+            assert_eq!(
+                pm_tcp_msg_to_lobby_command("create 5 4\r").ok(),
+                Some(super::super::super::lobby::LobbyCommand::Create(
+                    super::super::super::lobby::RoomOptions {
+                        size: 5,
+                        win_length: 4
+                    }
+                ))
+            );
+            assert_eq!(
+                pm_tcp_msg_to_lobby_command("create 3").ok(),
+                Some(super::super::super::lobby::LobbyCommand::Create(
+                    super::super::super::lobby::RoomOptions {
+                        size: 3,
+                        win_length: 3
+                    }
+                ))
+            );
+        }
+
+        #[test]
+        fn join_command() {
+            assert_eq!(
+                pm_tcp_msg_to_lobby_command("join 2").ok(),
+                Some(super::super::super::lobby::LobbyCommand::Join(
+                    super::super::super::lobby::RoomId(2)
+                ))
+            );
+            assert!(pm_tcp_msg_to_lobby_command("join").is_err());
+        }
+
+        #[test]
+        fn resume_command() {
+            assert_eq!(
+                pm_tcp_msg_to_lobby_command("resume abc123\r").ok(),
+                Some(super::super::super::lobby::LobbyCommand::Resume(
+                    "abc123".to_owned()
+                ))
+            );
+        }
+    }
+
+    mod test_pm_json_msg_to_lobby_command {
+        use super::super::*;
+
+        #[test]
+        fn create_command() {
+            assert_eq!(
+                pm_tcp_msg_to_lobby_command(r#"{"type": "create", "size": 5}"#).ok(),
+                Some(super::super::super::lobby::LobbyCommand::Create(
+                    super::super::super::lobby::RoomOptions {
+                        size: 5,
+                        win_length: 5
+                    }
+                ))
+            );
+        }
+    }
+
+    mod test_pm_json_msg_to_command {
+        use super::super::*;
+
+        #[test]
+        fn move_command() {
+            assert!(matches!(
+                pm_json_msg_to_command(r#"{"type": "move", "row": 2, "column": 3}"#),
+                Ok(crate::game::PlayerCommand::Move((1, 2)))
+            ));
+            assert!(pm_json_msg_to_command(r#"{"type": "move", "row": 0, "column": 3}"#).is_err());
+        }
+
+        #[test]
+        fn invalid_command() {
+            assert!(pm_json_msg_to_command("2-3").is_err());
+            assert!(pm_json_msg_to_command(r#"{"type": "jump"}"#).is_err());
+        }
+    }
+
+    mod test_pm_tcp_msg_to_command {
+        use super::super::*;
+
+        #[test]
+        fn move_command() {
+            assert!(matches!(
+                pm_tcp_msg_to_command("2-3\r"),
+                Ok(crate::game::PlayerCommand::Move((1, 2)))
+            ));
+        }
+
+        #[test]
+        fn swap_command() {
+            assert!(matches!(
+                pm_tcp_msg_to_command("swap"),
+                Ok(crate::game::PlayerCommand::Swap)
+            ));
+        }
+
+        #[test]
+        fn answer_command() {
+            assert!(matches!(
+                pm_tcp_msg_to_command("yes"),
+                Ok(crate::game::PlayerCommand::Yes)
+            ));
+            assert!(matches!(
+                pm_tcp_msg_to_command("n"),
+                Ok(crate::game::PlayerCommand::No)
+            ));
+        }
+
+        #[test]
+        fn resume_command() {
+            match pm_tcp_msg_to_command("resume abc123\r") {
+                Ok(crate::game::PlayerCommand::Resume(token)) => assert_eq!(token, "abc123"),
+                _ => panic!("Resume command was not parsed"),
+            }
+        }
+
+        #[test]
+        fn invalid_command() {
+            assert!(pm_tcp_msg_to_command("0-1").is_err());
+            assert!(pm_tcp_msg_to_command("swap it").is_err());
+        }
+    }
 }
@@ -0,0 +1,676 @@
+use super::player_manager::{
+    room, ClientId, MsgFromPlayer, MsgToPlayer, PlayerManagerTrait, PlayerTrait, Role,
+};
+
+/// Maximal size of playboard edge in room
+const MAX_PLAYBOARD_SIZE: usize = 10;
+
+/// Identifier of room
+#[derive(Debug, PartialEq, Clone, Copy, Hash, Eq, serde::Serialize)]
+pub struct RoomId(pub usize);
+
+impl std::fmt::Display for RoomId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Parameters of game chosen by creator of room
+#[derive(Debug, PartialEq, Clone, Copy, serde::Serialize)]
+pub struct RoomOptions {
+    /// Size of playboard edge
+    pub size: usize,
+    /// Number of fields in line required for win
+    pub win_length: usize,
+}
+
+impl RoomOptions {
+    /// Options of room for clients paired by matchmaking
+    fn from_queue_request(request: &QueueRequest) -> Self {
+        RoomOptions {
+            size: request.size,
+            win_length: request.size,
+        }
+    }
+
+    /// Check if game with options can be created
+    fn is_valid(&self) -> bool {
+        (3..=MAX_PLAYBOARD_SIZE).contains(&self.size)
+            && (super::playboard::pb_n_n::MIN_WIN_LENGTH..=self.size).contains(&self.win_length)
+    }
+}
+
+/// Description of room sended to clients in lobby
+#[derive(Debug, PartialEq, Clone, Copy, serde::Serialize)]
+pub struct RoomInfo {
+    pub id: RoomId,
+    pub options: RoomOptions,
+    /// Number of clients in room (players, spectators and challengers)
+    pub clients: usize,
+}
+
+/// Request of client for automatic pairing with opponent
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct QueueRequest {
+    /// Preferred size of playboard edge
+    pub size: usize,
+    /// Rating of client - None if client does not care about strength of opponent
+    pub rating: Option<u32>,
+}
+
+impl QueueRequest {
+    /// Check if two clients can be paired
+    fn matches(&self, other: &QueueRequest, rating_range: Option<u32>) -> bool {
+        if self.size != other.size {
+            return false;
+        }
+
+        match (self.rating, other.rating, rating_range) {
+            (Some(x), Some(y), Some(range)) => x.abs_diff(y) <= range,
+            _ => true,
+        }
+    }
+}
+
+/// Settings of lobby
+#[derive(Clone, Default)]
+pub struct Settings {
+    /// Size of playboard for clients queued automatically after connection - None for no queueing
+    pub matchmaking: Option<usize>,
+    /// Maximal difference of ratings of paired clients - None for any difference
+    pub rating_range: Option<u32>,
+    /// Stored ratings used instead of ratings sent by logged in clients
+    pub ratings: Option<std::sync::Arc<super::rating::RatingStore>>,
+}
+
+/// Possible commands of client in lobby
+#[derive(Debug, PartialEq)]
+pub enum LobbyCommand {
+    /// Create new room and join it
+    Create(RoomOptions),
+    /// List existing rooms
+    List,
+    /// Join existing room
+    Join(RoomId),
+    /// Enter queue for automatic pairing with opponent
+    Play(QueueRequest),
+    /// Leave queue
+    Cancel,
+    /// Return to interrupted game of room with session token
+    Resume(String),
+}
+
+/// Running room
+struct Room<P, M>
+where
+    P: PlayerTrait,
+{
+    options: RoomOptions,
+    tx: tokio::sync::mpsc::Sender<MsgFromPlayer<room::Player<P>, M>>,
+    task: tokio::task::JoinHandle<()>,
+    /// Number of clients in room
+    clients: usize,
+}
+
+/// Lobby distributing clients of player manager to rooms
+struct Lobby<T, F, C>
+where
+    T: PlayerManagerTrait,
+{
+    player_manager: T,
+    settings: Settings,
+    create_room: F,
+    convert_msg_to_command: C,
+    next_room_id: usize,
+    rooms: std::collections::HashMap<RoomId, Room<T::NewPlayer, T::PlayerMsg>>,
+    /// Clients which did not join any room
+    guests: std::collections::HashMap<ClientId, T::NewPlayer>,
+    /// Rooms of clients which joined some room
+    locations: std::collections::HashMap<ClientId, RoomId>,
+    /// Clients waiting for opponent in order of arrival
+    queue: Vec<(ClientId, QueueRequest)>,
+    /// Clients which disconnected while they were in room - they do not return to lobby
+    disconnected: std::collections::HashSet<ClientId>,
+    /// Rooms waiting for disconnected players by their session tokens
+    session_tokens: std::collections::HashMap<String, RoomId>,
+    tx_left: tokio::sync::mpsc::UnboundedSender<room::LeftPlayer<T::NewPlayer>>,
+}
+
+impl<T, F, C> Lobby<T, F, C>
+where
+    T: PlayerManagerTrait,
+    T::PlayerMsg: Clone,
+    F: Fn(
+        RoomOptions,
+        room::PlayerManager<T::NewPlayer, T::PlayerMsg>,
+    ) -> tokio::task::JoinHandle<()>,
+    C: Fn(T::PlayerMsg) -> super::converters::ConversionResult<LobbyCommand>,
+{
+    async fn handle_join(&mut self, client_id: ClientId, new_player_data: T::NewPlayerData) {
+        let player = self
+            .player_manager
+            .create_new_player(client_id, Role::Guest, new_player_data);
+
+        self.welcome_guest(player).await;
+    }
+
+    /// Add new client or client returned from room to lobby
+    async fn welcome_guest(&mut self, mut player: T::NewPlayer) {
+        let client_id = player.get_client_id();
+
+        player.send_msg_to_player(MsgToPlayer::WelcomeLobby).await;
+        self.guests.insert(client_id, player);
+
+        if let Some(size) = self.settings.matchmaking {
+            let request = QueueRequest { size, rating: None };
+            self.enter_queue(client_id, request).await;
+        }
+    }
+
+    async fn handle_msg(&mut self, client_id: ClientId, msg: T::PlayerMsg) {
+        if let Some(room_id) = self.locations.get(&client_id) {
+            let _ = self.rooms[room_id]
+                .tx
+                .send(MsgFromPlayer::Msg(client_id, msg))
+                .await;
+            return;
+        }
+
+        let player = match self.guests.get_mut(&client_id) {
+            Some(x) => x,
+            None => return,
+        };
+
+        match (self.convert_msg_to_command)(msg.clone()) {
+            Ok(LobbyCommand::Create(options)) if options.is_valid() => {
+                let room_id = self.create_new_room(options);
+                self.guests
+                    .get_mut(&client_id)
+                    .unwrap()
+                    .send_msg_to_player(MsgToPlayer::RoomCreated(room_id))
+                    .await;
+                self.join_room(client_id, room_id, None).await;
+            }
+            Ok(LobbyCommand::List) => {
+                let mut rooms: Vec<RoomInfo> = self
+                    .rooms
+                    .iter()
+                    .map(|(id, room)| RoomInfo {
+                        id: *id,
+                        options: room.options,
+                        clients: room.clients,
+                    })
+                    .collect();
+                rooms.sort_by_key(|x| x.id.0);
+
+                player.send_msg_to_player(MsgToPlayer::Rooms(rooms)).await;
+            }
+            Ok(LobbyCommand::Join(room_id)) if self.rooms.contains_key(&room_id) => {
+                self.join_room(client_id, room_id, None).await;
+            }
+            Ok(LobbyCommand::Join(_)) => {
+                player.send_msg_to_player(MsgToPlayer::RoomNotFound).await;
+            }
+            Ok(LobbyCommand::Play(request))
+                if RoomOptions::from_queue_request(&request).is_valid() =>
+            {
+                self.enter_queue(client_id, request).await;
+            }
+            Ok(LobbyCommand::Cancel) => {
+                let queued = self.queue.len();
+                self.queue.retain(|(id, _)| *id != client_id);
+
+                match self.queue.len() != queued {
+                    true => player.send_msg_to_player(MsgToPlayer::QueueLeft).await,
+                    false => player.send_msg_to_player(MsgToPlayer::InvalidInput).await,
+                }
+            }
+            Ok(LobbyCommand::Resume(token)) => match self.session_tokens.remove(&token) {
+                Some(room_id) => {
+                    self.join_room(client_id, room_id, Some(token)).await;
+
+                    // Session token is checked by game of room
+                    let _ = self.rooms[&room_id]
+                        .tx
+                        .send(MsgFromPlayer::Msg(client_id, msg))
+                        .await;
+                }
+                None => {
+                    player
+                        .send_msg_to_player(MsgToPlayer::InvalidSessionToken)
+                        .await
+                }
+            },
+            _ => player.send_msg_to_player(MsgToPlayer::InvalidInput).await,
+        }
+    }
+
+    /// Pair client with waiting opponent or put it to queue
+    async fn enter_queue(&mut self, client_id: ClientId, mut request: QueueRequest) {
+        self.leave_queue(client_id);
+
+        if let Some(rating) = self.stored_rating(client_id, request.size) {
+            request.rating = Some(rating);
+        }
+
+        let opponent = self
+            .queue
+            .iter()
+            .position(|(_, x)| x.matches(&request, self.settings.rating_range));
+
+        let opponent_id = match opponent {
+            Some(index) => self.queue.remove(index).0,
+            None => {
+                log::info!(
+                    "Client {} is searching opponent for playboard {}x{}",
+                    client_id,
+                    request.size,
+                    request.size
+                );
+                self.queue.push((client_id, request));
+                self.guests
+                    .get_mut(&client_id)
+                    .unwrap()
+                    .send_msg_to_player(MsgToPlayer::SearchingOpponent(request.size))
+                    .await;
+                return;
+            }
+        };
+
+        let room_id = self.create_new_room(RoomOptions::from_queue_request(&request));
+        log::info!(
+            "Clients {} and {} were paired in room {}",
+            opponent_id,
+            client_id,
+            room_id
+        );
+
+        for id in [opponent_id, client_id] {
+            self.guests
+                .get_mut(&id)
+                .unwrap()
+                .send_msg_to_player(MsgToPlayer::OpponentFound(room_id))
+                .await;
+            self.join_room(id, room_id, None).await;
+        }
+    }
+
+    fn leave_queue(&mut self, client_id: ClientId) {
+        self.queue.retain(|(id, _)| *id != client_id);
+    }
+
+    /// Rating of logged in client for games of matchmaking with given size
+    fn stored_rating(&self, client_id: ClientId, size: usize) -> Option<u32> {
+        let store = self.settings.ratings.as_ref()?;
+        let account = self.guests.get(&client_id)?.get_account()?;
+        let pool = super::rating::Pool {
+            size,
+            win_length: size,
+        };
+
+        Some(store.get(&pool, account).max(0) as u32)
+    }
+
+    /// Start game task of new room
+    fn create_new_room(&mut self, options: RoomOptions) -> RoomId {
+        let room_id = RoomId(self.next_room_id);
+        self.next_room_id += 1;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
+        let task = (self.create_room)(options, room::PlayerManager::new(rx));
+
+        log::info!(
+            "Room {} created with playboard {}x{} and win length {}",
+            room_id,
+            options.size,
+            options.size,
+            options.win_length
+        );
+
+        self.rooms.insert(
+            room_id,
+            Room {
+                options,
+                tx,
+                task,
+                clients: 0,
+            },
+        );
+
+        room_id
+    }
+
+    /// Move client from lobby to room (client returning to interrupted game has session token)
+    async fn join_room(
+        &mut self,
+        client_id: ClientId,
+        room_id: RoomId,
+        session_token: Option<String>,
+    ) {
+        self.leave_queue(client_id);
+        let player = self.guests.remove(&client_id).unwrap();
+        let room = self.rooms.get_mut(&room_id).unwrap();
+
+        log::info!("Client {} joined room {}", client_id, room_id);
+
+        room.clients += 1;
+        self.locations.insert(client_id, room_id);
+
+        let mut player = room::Player::new(player, self.tx_left.clone());
+        if let Some(token) = session_token {
+            player.set_session_token(token);
+        }
+        let _ = room.tx.send(MsgFromPlayer::Join(client_id, player)).await;
+    }
+
+    async fn handle_leave(&mut self, client_id: ClientId) {
+        if self.guests.remove(&client_id).is_some() {
+            return self.leave_queue(client_id);
+        }
+
+        if let Some(room_id) = self.locations.get(&client_id) {
+            self.disconnected.insert(client_id);
+            let _ = self.rooms[room_id]
+                .tx
+                .send(MsgFromPlayer::Leave(client_id))
+                .await;
+        }
+    }
+
+    /// Client was dropped by game of room - connection of disconnected client is closed, other
+    /// clients return to lobby
+    async fn handle_left_room(&mut self, left: room::LeftPlayer<T::NewPlayer>) {
+        let mut player = left.player;
+        let client_id = player.get_client_id();
+
+        let room_id = match self.locations.remove(&client_id) {
+            Some(x) => x,
+            None => return,
+        };
+
+        let room = self.rooms.get_mut(&room_id).unwrap();
+        room.clients -= 1;
+
+        if room.clients == 0 {
+            room.task.abort();
+            self.rooms.remove(&room_id);
+            self.session_tokens.retain(|_, x| *x != room_id);
+            log::info!("Room {} closed", room_id);
+        }
+
+        match self.disconnected.remove(&client_id) {
+            // Room may hold seat of disconnected player for its new connection
+            true => {
+                if let (Some(token), true) = (left.session_token, self.rooms.contains_key(&room_id))
+                {
+                    self.session_tokens.insert(token, room_id);
+                }
+            }
+            false => {
+                log::info!(
+                    "Client {} returned from room {} to lobby",
+                    client_id,
+                    room_id
+                );
+                player.set_role(Role::Guest);
+                self.welcome_guest(player).await;
+            }
+        }
+    }
+}
+
+/// Run lobby where clients can create and join rooms with own games
+pub async fn run_lobby<T, F, C>(
+    player_manager: T,
+    settings: Settings,
+    create_room: F,
+    convert_msg_to_command: C,
+) where
+    T: PlayerManagerTrait,
+    T::PlayerMsg: Clone,
+    F: Fn(
+        RoomOptions,
+        room::PlayerManager<T::NewPlayer, T::PlayerMsg>,
+    ) -> tokio::task::JoinHandle<()>,
+    C: Fn(T::PlayerMsg) -> super::converters::ConversionResult<LobbyCommand>,
+{
+    let (tx_left, mut rx_left) = tokio::sync::mpsc::unbounded_channel();
+
+    let mut lobby = Lobby {
+        player_manager,
+        settings,
+        create_room,
+        convert_msg_to_command,
+        next_room_id: 1,
+        rooms: std::collections::HashMap::new(),
+        guests: std::collections::HashMap::new(),
+        locations: std::collections::HashMap::new(),
+        queue: Vec::new(),
+        disconnected: std::collections::HashSet::new(),
+        session_tokens: std::collections::HashMap::new(),
+        tx_left,
+    };
+
+    loop {
+        tokio::select! {
+            msg = lobby.player_manager.receive_new_message() => match msg {
+                MsgFromPlayer::Join(client_id, new_player_data) => {
+                    lobby.handle_join(client_id, new_player_data).await
+                }
+                MsgFromPlayer::Msg(client_id, msg) => lobby.handle_msg(client_id, msg).await,
+                MsgFromPlayer::Leave(client_id) => lobby.handle_leave(client_id).await,
+            },
+            // Lobby keeps sender, so channel is never closed
+            Some(left) = rx_left.recv() => lobby.handle_left_room(left).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Messages sent to clients of lobby
+    type SentMessages = tokio::sync::mpsc::UnboundedReceiver<(ClientId, MsgToPlayer<()>)>;
+
+    /// Player reporting messages to channel
+    struct TestPlayer {
+        role: Role,
+        client_id: ClientId,
+        tx: tokio::sync::mpsc::UnboundedSender<(ClientId, MsgToPlayer<()>)>,
+    }
+
+    #[async_trait::async_trait]
+    impl PlayerTrait for TestPlayer {
+        type FieldRepresentation = ();
+
+        async fn send_msg_to_player(&mut self, msg: MsgToPlayer<Self::FieldRepresentation>) {
+            let _ = self.tx.send((self.client_id, msg));
+        }
+
+        fn get_role(&self) -> Role {
+            self.role
+        }
+
+        fn set_role(&mut self, role: Role) {
+            self.role = role;
+        }
+
+        fn get_nickname(&self) -> Option<&str> {
+            None
+        }
+
+        fn set_nickname(&mut self, _nickname: String) {}
+
+        fn get_account(&self) -> Option<&str> {
+            None
+        }
+
+        fn get_client_id(&self) -> ClientId {
+            self.client_id
+        }
+    }
+
+    /// Player manager of lobby - messages of clients are passed to lobby directly by test
+    struct TestPlayerManager {
+        tx: tokio::sync::mpsc::UnboundedSender<(ClientId, MsgToPlayer<()>)>,
+    }
+
+    #[async_trait::async_trait]
+    impl PlayerManagerTrait for TestPlayerManager {
+        type NewPlayerData = ();
+        type NewPlayer = TestPlayer;
+        type PlayerMsg = String;
+
+        fn create_new_player(
+            &self,
+            client_id: ClientId,
+            role: Role,
+            _player_data: Self::NewPlayerData,
+        ) -> Self::NewPlayer {
+            TestPlayer {
+                role,
+                client_id,
+                tx: self.tx.clone(),
+            }
+        }
+
+        async fn receive_new_message(
+            &mut self,
+        ) -> MsgFromPlayer<Self::NewPlayerData, Self::PlayerMsg> {
+            std::future::pending().await
+        }
+    }
+
+    type RoomPlayerManager = room::PlayerManager<TestPlayer, String>;
+
+    /// Lobby with player managers of created rooms and players returned from rooms
+    #[allow(clippy::type_complexity)]
+    fn lobby() -> (
+        Lobby<
+            TestPlayerManager,
+            impl Fn(RoomOptions, RoomPlayerManager) -> tokio::task::JoinHandle<()>,
+            impl Fn(String) -> super::super::converters::ConversionResult<LobbyCommand>,
+        >,
+        SentMessages,
+        tokio::sync::mpsc::UnboundedReceiver<RoomPlayerManager>,
+        tokio::sync::mpsc::UnboundedReceiver<room::LeftPlayer<TestPlayer>>,
+    ) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_rooms, rx_rooms) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_left, rx_left) = tokio::sync::mpsc::unbounded_channel();
+
+        let lobby = Lobby {
+            player_manager: TestPlayerManager { tx },
+            settings: Settings::default(),
+            // Test plays for game of room
+            create_room: move |_, player_manager| {
+                let _ = tx_rooms.send(player_manager);
+                tokio::spawn(async {})
+            },
+            convert_msg_to_command: super::super::converters::pm_tcp_msg_to_lobby_command,
+            next_room_id: 1,
+            rooms: std::collections::HashMap::new(),
+            guests: std::collections::HashMap::new(),
+            locations: std::collections::HashMap::new(),
+            queue: Vec::new(),
+            disconnected: std::collections::HashSet::new(),
+            session_tokens: std::collections::HashMap::new(),
+            tx_left,
+        };
+
+        (lobby, rx, rx_rooms, rx_left)
+    }
+
+    async fn join_room(room: &mut RoomPlayerManager) -> room::Player<TestPlayer> {
+        match room.receive_new_message().await {
+            MsgFromPlayer::Join(_, player) => player,
+            _ => panic!("Client did not join room"),
+        }
+    }
+
+    #[tokio::test]
+    async fn player_leaving_room_returns_to_lobby() {
+        let (mut lobby, mut rx, mut rx_rooms, mut rx_left) = lobby();
+
+        lobby.handle_join(ClientId(0), ()).await;
+        lobby.handle_msg(ClientId(0), "create 3".to_owned()).await;
+        assert!(matches!(rx.try_recv(), Ok((_, MsgToPlayer::WelcomeLobby))));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok((_, MsgToPlayer::RoomCreated(_)))
+        ));
+
+        // Game drops player which does not want to play anymore
+        let mut room = rx_rooms.try_recv().unwrap();
+        drop(join_room(&mut room).await);
+        lobby.handle_left_room(rx_left.try_recv().unwrap()).await;
+
+        assert!(matches!(
+            rx.try_recv(),
+            Ok((ClientId(0), MsgToPlayer::WelcomeLobby))
+        ));
+        assert_eq!(lobby.guests[&ClientId(0)].get_role(), Role::Guest);
+        assert!(lobby.rooms.is_empty());
+    }
+
+    #[tokio::test]
+    async fn disconnected_player_resumes_game_by_session_token() {
+        let (mut lobby, mut rx, mut rx_rooms, mut rx_left) = lobby();
+
+        lobby.handle_join(ClientId(0), ()).await;
+        lobby.handle_join(ClientId(1), ()).await;
+        lobby.handle_msg(ClientId(0), "create 3".to_owned()).await;
+        lobby.handle_msg(ClientId(1), "join 1".to_owned()).await;
+
+        let mut room = rx_rooms.try_recv().unwrap();
+        let mut player = join_room(&mut room).await;
+        let _other_player = join_room(&mut room).await;
+        player
+            .send_msg_to_player(MsgToPlayer::SessionToken("abc".to_owned()))
+            .await;
+
+        // Disconnected client does not return to lobby
+        lobby.handle_leave(ClientId(0)).await;
+        assert!(matches!(
+            room.receive_new_message().await,
+            MsgFromPlayer::Leave(ClientId(0))
+        ));
+        drop(player);
+        lobby.handle_left_room(rx_left.try_recv().unwrap()).await;
+        assert!(!lobby.guests.contains_key(&ClientId(0)));
+
+        // New connection of client is moved to room with its session token
+        lobby.handle_join(ClientId(2), ()).await;
+        lobby.handle_msg(ClientId(2), "resume abc".to_owned()).await;
+        assert_eq!(join_room(&mut room).await.get_client_id(), ClientId(2));
+        assert!(matches!(
+            room.receive_new_message().await,
+            MsgFromPlayer::Msg(ClientId(2), msg) if msg == "resume abc"
+        ));
+
+        // Token can be used only once
+        lobby.handle_join(ClientId(3), ()).await;
+        lobby.handle_msg(ClientId(3), "resume abc".to_owned()).await;
+        let mut last = None;
+        while let Ok(x) = rx.try_recv() {
+            last = Some(x);
+        }
+        assert!(matches!(
+            last,
+            Some((ClientId(3), MsgToPlayer::InvalidSessionToken))
+        ));
+        assert!(lobby.guests.contains_key(&ClientId(3)));
+    }
+
+    #[test]
+    fn pairing_by_size_and_rating() {
+        let request = |size, rating| QueueRequest { size, rating };
+
+        assert!(request(3, None).matches(&request(3, Some(1500)), Some(100)));
+        assert!(!request(3, None).matches(&request(4, None), None));
+        assert!(request(3, Some(1500)).matches(&request(3, Some(1600)), Some(100)));
+        assert!(!request(3, Some(1500)).matches(&request(3, Some(1601)), Some(100)));
+        assert!(request(3, Some(1500)).matches(&request(3, Some(2000)), None));
+    }
+}
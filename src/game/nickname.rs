@@ -0,0 +1,69 @@
+/// Minimal number of characters in nickname
+pub const MIN_LENGTH: usize = 3;
+/// Maximal number of characters in nickname
+pub const MAX_LENGTH: usize = 16;
+
+/// Enum for reason of rejected nickname
+#[derive(Debug, PartialEq, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NicknameError {
+    /// Nickname is too short or too long
+    InvalidLength,
+    /// Nickname contains other characters than letters, digits, "-" and "_"
+    InvalidCharacters,
+    /// Nickname is used by other player
+    AlreadyUsed,
+}
+
+/// Check if nickname can be used by new player
+pub fn validate<'a, T>(nickname: &str, mut used: T) -> Result<(), NicknameError>
+where
+    T: Iterator<Item = &'a str>,
+{
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&nickname.chars().count()) {
+        return Err(NicknameError::InvalidLength);
+    }
+
+    if !nickname
+        .chars()
+        .all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_')
+    {
+        return Err(NicknameError::InvalidCharacters);
+    }
+
+    // Nicknames differing only in case would be confusing
+    match used.any(|x| x.eq_ignore_ascii_case(nickname)) {
+        true => Err(NicknameError::AlreadyUsed),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn valid_nickname() {
+        assert_eq!(validate("player_1", ["other"].into_iter()), Ok(()));
+    }
+
+    #[test]
+    fn invalid_nickname() {
+        assert_eq!(
+            validate("ab", std::iter::empty()),
+            Err(NicknameError::InvalidLength)
+        );
+        assert_eq!(
+            validate("a very long nickname", std::iter::empty()),
+            Err(NicknameError::InvalidLength)
+        );
+        assert_eq!(
+            validate("no spaces", std::iter::empty()),
+            Err(NicknameError::InvalidCharacters)
+        );
+        assert_eq!(
+            validate("Player", ["player"].into_iter()),
+            Err(NicknameError::AlreadyUsed)
+        );
+    }
+}
@@ -76,7 +76,82 @@ where
     }
 }
 
+// ---- Lines shorter than playboard edge ----
+/// Directions of lines - row, column, negative and positive diagonal
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+/// Get field moved by offset in direction - None if it is out of playboard
+fn get_shifted_index(
+    field: &Playboard,
+    position: (usize, usize),
+    direction: (isize, isize),
+    offset: isize,
+) -> Option<usize> {
+    let row = position.0 as isize + direction.0 * offset;
+    let col = position.1 as isize + direction.1 * offset;
+    let edge_size = field.edge_size as isize;
+
+    match (0..edge_size).contains(&row) && (0..edge_size).contains(&col) {
+        true => Some((row * edge_size + col) as usize),
+        false => None,
+    }
+}
+
+/// Count fields of player following position in direction
+fn count_in_direction(
+    field: &Playboard,
+    position: (usize, usize),
+    direction: (isize, isize),
+    player: &super::super::PlayerId,
+) -> usize {
+    (1..)
+        .map_while(|offset| get_shifted_index(field, position, direction, offset))
+        .take_while(|index| field.fields[*index].used_by_user(player))
+        .count()
+}
+
+fn check_for_win_length(field: &Playboard, position: (usize, usize)) -> bool {
+    let player = match field.fields[position.0 * field.edge_size + position.1].field {
+        Some(x) => x,
+        None => return false,
+    };
+
+    DIRECTIONS.iter().any(|direction| {
+        let opposite = (-direction.0, -direction.1);
+
+        1 + count_in_direction(field, position, *direction, &player)
+            + count_in_direction(field, position, opposite, &player)
+            >= field.win_length
+    })
+}
+
+fn check_for_draw_win_length(field: &Playboard) -> bool {
+    let line_length = field.win_length as isize - 1;
+
+    let positions =
+        (0..field.edge_size).flat_map(|row| (0..field.edge_size).map(move |col| (row, col)));
+
+    !positions
+        .flat_map(|position| {
+            DIRECTIONS
+                .iter()
+                .map(move |direction| (position, *direction))
+        })
+        .filter(|(position, direction)| {
+            get_shifted_index(field, *position, *direction, line_length).is_some()
+        })
+        .any(|(position, direction)| {
+            is_line_capable((0..=line_length).map(|offset| {
+                &field.fields[get_shifted_index(field, position, direction, offset).unwrap()]
+            }))
+        })
+}
+
 fn check_for_win(field: &Playboard, position: (usize, usize)) -> bool {
+    if field.win_length < field.edge_size {
+        return check_for_win_length(field, position);
+    }
+
     // Check row and column
     if are_same_some_values(get_iter_row(field, position.0))
         || are_same_some_values(get_iter_col_(field, position.1))
@@ -103,6 +178,7 @@ fn check_for_win(field: &Playboard, position: (usize, usize)) -> bool {
 }
 
 // ---- Check field for draw ----
+#[allow(clippy::single_match)]
 fn is_line_capable<'a, T>(data: T) -> bool
 where
     T: Iterator<Item = &'a SingleField>,
@@ -127,6 +203,10 @@ where
 }
 
 fn check_for_draw(field: &Playboard) -> bool {
+    if field.win_length < field.edge_size {
+        return check_for_draw_win_length(field);
+    }
+
     let rows_capable =
         (0..field.edge_size).any(|index| is_line_capable(get_iter_row(field, index)));
 
@@ -150,6 +230,7 @@ fn check_for_draw(field: &Playboard) -> bool {
 pub struct PlayboardToBig {}
 
 impl std::fmt::Display for PlayboardToBig {
+    #[allow(clippy::needless_return)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         return write!(f, "Playboard nxn is to big");
     }
@@ -157,9 +238,29 @@ impl std::fmt::Display for PlayboardToBig {
 
 impl std::error::Error for PlayboardToBig {}
 
+/// Shortest line required for win
+pub const MIN_WIN_LENGTH: usize = 3;
+
+#[derive(Debug)]
+pub struct InvalidWinLength {}
+
+impl std::fmt::Display for InvalidWinLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Win length has to be between {} and size of playboard edge",
+            MIN_WIN_LENGTH
+        )
+    }
+}
+
+impl std::error::Error for InvalidWinLength {}
+
 pub struct Playboard {
     pub fields: Vec<SingleField>,
     pub edge_size: usize,
+    /// Number of fields in line required for win
+    pub win_length: usize,
 }
 
 impl Playboard {
@@ -167,11 +268,25 @@ impl Playboard {
         match edge_size.checked_mul(edge_size) {
             Some(x) => Ok(Playboard {
                 fields: (0..x).map(|_| SingleField::new()).collect(),
-                edge_size: edge_size,
+                edge_size,
+                win_length: edge_size,
             }),
             None => Err(Box::new(PlayboardToBig {})),
         }
     }
+
+    pub fn with_win_length(
+        edge_size: usize,
+        win_length: usize,
+    ) -> Result<Playboard, Box<dyn std::error::Error>> {
+        if !(MIN_WIN_LENGTH..=edge_size).contains(&win_length) {
+            return Err(Box::new(InvalidWinLength {}));
+        }
+
+        let mut playboard = Self::new(edge_size)?;
+        playboard.win_length = win_length;
+        Ok(playboard)
+    }
 }
 
 fn check_range(value: usize, limit: usize) -> bool {
@@ -181,6 +296,7 @@ fn check_range(value: usize, limit: usize) -> bool {
 impl super::Playboard for Playboard {
     type Position = (usize, usize);
 
+    #[allow(clippy::needless_borrow)]
     fn new_move(
         &mut self,
         position: Self::Position,
@@ -214,3 +330,56 @@ impl super::Playboard for Playboard {
         Ok(super::ValidMove::Continue)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::super::Playboard as _;
+    use super::*;
+
+    const CIRCLE: super::super::super::PlayerId = super::super::super::PlayerId::Circle;
+    const CROSS: super::super::super::PlayerId = super::super::super::PlayerId::Cross;
+
+    #[test]
+    fn win_with_shorter_line() {
+        let mut playboard = Playboard::with_win_length(5, 3).unwrap();
+
+        assert!(matches!(
+            playboard.new_move((1, 1), CIRCLE),
+            Ok(super::super::ValidMove::Continue)
+        ));
+        assert!(matches!(
+            playboard.new_move((3, 3), CIRCLE),
+            Ok(super::super::ValidMove::Continue)
+        ));
+        assert!(matches!(
+            playboard.new_move((2, 2), CIRCLE),
+            Ok(super::super::ValidMove::Win)
+        ));
+    }
+
+    #[test]
+    fn draw_with_shorter_line() {
+        let mut playboard = Playboard::with_win_length(4, 3).unwrap();
+        let rows = ["ooxx", "xxoo", "ooxx", "xxoo"];
+
+        let mut result = None;
+        for (row, fields) in rows.iter().enumerate() {
+            for (col, field) in fields.chars().enumerate() {
+                let player_id = match field {
+                    'o' => CIRCLE,
+                    _ => CROSS,
+                };
+                result = Some(playboard.new_move((row, col), player_id));
+                assert!(!matches!(result, Some(Ok(super::super::ValidMove::Win))));
+            }
+        }
+
+        assert!(matches!(result, Some(Ok(super::super::ValidMove::Draw))));
+    }
+
+    #[test]
+    fn invalid_win_length() {
+        assert!(Playboard::with_win_length(3, 4).is_err());
+        assert!(Playboard::with_win_length(5, 2).is_err());
+    }
+}
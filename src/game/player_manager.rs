@@ -1,43 +1,308 @@
 /// Module for player manager traits
+pub mod composite;
+pub mod http;
+pub mod local;
+pub mod room;
 pub mod tcp;
+#[cfg(unix)]
+pub mod unix;
+pub mod websocket;
+
+/// Identifier of connected client assigned by player manager
+#[derive(Debug, PartialEq, Clone, Copy, Hash, Eq)]
+pub struct ClientId(pub usize);
+
+impl std::fmt::Display for ClientId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Role of client in game
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Role {
+    /// Client plays for given side
+    Player(super::PlayerId),
+    /// Client only watches game
+    Spectator,
+    /// Client waits for free seat
+    Challenger,
+    /// Client is in lobby and did not join any room
+    Guest,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Player(player_id) => write!(f, "{}", player_id),
+            Self::Spectator => write!(f, "spectator"),
+            Self::Challenger => write!(f, "challenger"),
+            Self::Guest => write!(f, "lobby"),
+        }
+    }
+}
+
+/// Player introduced to other clients before game
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+pub struct PlayerInfo {
+    pub nickname: String,
+    /// Rating of logged in player in pool of game
+    pub rating: Option<i32>,
+}
 
 /// Possible messages sended to player
+///
+/// Structured protocols send message as JSON object with "type" (variant name in snake case)
+/// and optional "data" field.
+#[derive(serde::Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
 pub enum MsgToPlayer<T> {
+    /// Welcome to new client in lobby
+    WelcomeLobby,
+    /// Room was created
+    RoomCreated(super::lobby::RoomId),
+    /// Existing rooms
+    Rooms(Vec<super::lobby::RoomInfo>),
+    /// Required room does not exist
+    RoomNotFound,
+    /// Client waits in queue for opponent for playboard of given size
+    SearchingOpponent(usize),
+    /// Client left queue
+    QueueLeft,
+    /// Client was paired with opponent in room
+    OpponentFound(super::lobby::RoomId),
+    /// Welcome to registered participant of tournament
+    WelcomeTournament,
+    /// Client is not registered participant of tournament (or participant is already connected)
+    NotParticipant,
+    /// Participant waits for start of next round
+    WaitingForNextRound,
+    /// Round with given number (of total number of rounds) starts
+    RoundStarted(usize, usize),
+    /// Participant has no opponent in round and gets point
+    Bye,
+    /// Opponent did not play game and participant wins
+    OpponentForfeited,
+    /// Current standings of tournament
+    Standings(Vec<super::tournament::standings::Standing>),
     /// Welcome to new player
     WelcomePlayer,
+    /// Welcome to new spectator
+    WelcomeSpectator,
+    /// Number of spectators watching game
+    Spectators(usize),
+    /// Spectator is not allowed to play
+    SpectatorsCannotPlay,
+    /// All places are taken - connection will be closed
+    ServerFull,
+    /// All places are taken - client waits in queue on given position
+    QueuedAsChallenger(usize),
+    /// Token for reconnection to interrupted game
+    SessionToken(String),
+    /// Choose your nickname
+    EnterNickname,
+    /// Nickname can not be used
+    InvalidNickname(super::nickname::NicknameError),
+    /// Game is interrupted - session token of disconnected player is required
+    EnterSessionToken,
+    /// Session token is not valid
+    InvalidSessionToken,
+    /// Interrupted game continues
+    GameResumed,
     /// Waiting for another player
     WaitingForOtherPlayer,
     /// Both players are ready
-    PlayersAreReady,
+    PlayersAreReady {
+        circle: PlayerInfo,
+        cross: PlayerInfo,
+    },
     /// Other player leave game
     OtherPlayerLeave,
+    /// Other player disconnected and has given time for reconnection
+    OtherPlayerDisconnected(
+        #[serde(serialize_with = "super::clock::serialize_seconds")] std::time::Duration,
+    ),
+    /// Other player reconnected to game
+    OtherPlayerReconnected,
     /// You are on move
     YourAreOnMove,
-    /// Other player is on move
-    OtherPlayerIsOnMove,
+    /// Other player (with given nickname) is on move
+    OtherPlayerIsOnMove(String),
     /// Player use invalid input
     InvalidInput,
     /// Field is already taken
     AlreadyTaken,
     /// You are not on move
     YouAreNotOnMove,
+    /// You may swap sides instead of your move
+    SwapAvailable,
+    /// Swap of sides is not possible now
+    SwapNotAvailable,
+    /// Players swapped sides
+    SidesSwapped,
+    /// Remaining time of players
+    Clock(super::clock::ClockState),
+    /// You sent draw offer to other player
+    DrawOfferSent,
+    /// Other player offers draw
+    DrawOffered,
+    /// Other player rejected your draw offer
+    DrawOfferDeclined,
     /// You win
-    YouWon,
+    YouWon(super::ResultReason),
     /// You lose
-    YouLose,
+    YouLose(super::ResultReason),
+    /// Player with nickname won game (sended to spectators)
+    PlayerWon(super::PlayerId, String, super::ResultReason),
     /// Draw
-    Draw,
+    Draw(super::ResultReason),
+    /// Your new rating after game
+    RatingChanged(super::rating::RatingChange),
+    /// Best rated accounts
+    Leaderboard(Vec<super::rating::LeaderboardEntry>),
+    /// Current score of match
+    Score(super::scoreboard::Score),
+    /// You win match
+    YouWonMatch,
+    /// You lose match
+    YouLostMatch,
+    /// Match ended and nobody win
+    MatchDrawn,
+    /// Do you want to play again?
+    RematchQuestion,
+    /// Waiting for answer of other player to rematch
+    WaitingForRematchAnswer,
+    /// Other player wants to play again
+    OtherPlayerWantsRematch,
+    /// Other player does not want to play again
+    OtherPlayerDeclinedRematch,
+    /// You are disconnected from game
+    Goodbye,
     /// Send playboard
     Playboard(T),
 }
+impl<T> MsgToPlayer<T> {
+    /// Convert playboard of message to other representation (other messages are kept)
+    pub fn map_playboard<U, F>(self, f: F) -> MsgToPlayer<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        match self {
+            Self::WelcomeLobby => MsgToPlayer::WelcomeLobby,
+            Self::RoomCreated(x) => MsgToPlayer::RoomCreated(x),
+            Self::Rooms(x) => MsgToPlayer::Rooms(x),
+            Self::RoomNotFound => MsgToPlayer::RoomNotFound,
+            Self::SearchingOpponent(x) => MsgToPlayer::SearchingOpponent(x),
+            Self::QueueLeft => MsgToPlayer::QueueLeft,
+            Self::OpponentFound(x) => MsgToPlayer::OpponentFound(x),
+            Self::WelcomeTournament => MsgToPlayer::WelcomeTournament,
+            Self::NotParticipant => MsgToPlayer::NotParticipant,
+            Self::WaitingForNextRound => MsgToPlayer::WaitingForNextRound,
+            Self::RoundStarted(a, b) => MsgToPlayer::RoundStarted(a, b),
+            Self::Bye => MsgToPlayer::Bye,
+            Self::OpponentForfeited => MsgToPlayer::OpponentForfeited,
+            Self::Standings(x) => MsgToPlayer::Standings(x),
+            Self::WelcomePlayer => MsgToPlayer::WelcomePlayer,
+            Self::WelcomeSpectator => MsgToPlayer::WelcomeSpectator,
+            Self::Spectators(x) => MsgToPlayer::Spectators(x),
+            Self::SpectatorsCannotPlay => MsgToPlayer::SpectatorsCannotPlay,
+            Self::ServerFull => MsgToPlayer::ServerFull,
+            Self::QueuedAsChallenger(x) => MsgToPlayer::QueuedAsChallenger(x),
+            Self::SessionToken(x) => MsgToPlayer::SessionToken(x),
+            Self::EnterNickname => MsgToPlayer::EnterNickname,
+            Self::InvalidNickname(x) => MsgToPlayer::InvalidNickname(x),
+            Self::EnterSessionToken => MsgToPlayer::EnterSessionToken,
+            Self::InvalidSessionToken => MsgToPlayer::InvalidSessionToken,
+            Self::GameResumed => MsgToPlayer::GameResumed,
+            Self::WaitingForOtherPlayer => MsgToPlayer::WaitingForOtherPlayer,
+            Self::PlayersAreReady { circle, cross } => {
+                MsgToPlayer::PlayersAreReady { circle, cross }
+            }
+            Self::OtherPlayerLeave => MsgToPlayer::OtherPlayerLeave,
+            Self::OtherPlayerDisconnected(x) => MsgToPlayer::OtherPlayerDisconnected(x),
+            Self::OtherPlayerReconnected => MsgToPlayer::OtherPlayerReconnected,
+            Self::YourAreOnMove => MsgToPlayer::YourAreOnMove,
+            Self::OtherPlayerIsOnMove(x) => MsgToPlayer::OtherPlayerIsOnMove(x),
+            Self::InvalidInput => MsgToPlayer::InvalidInput,
+            Self::AlreadyTaken => MsgToPlayer::AlreadyTaken,
+            Self::YouAreNotOnMove => MsgToPlayer::YouAreNotOnMove,
+            Self::SwapAvailable => MsgToPlayer::SwapAvailable,
+            Self::SwapNotAvailable => MsgToPlayer::SwapNotAvailable,
+            Self::SidesSwapped => MsgToPlayer::SidesSwapped,
+            Self::Clock(x) => MsgToPlayer::Clock(x),
+            Self::DrawOfferSent => MsgToPlayer::DrawOfferSent,
+            Self::DrawOffered => MsgToPlayer::DrawOffered,
+            Self::DrawOfferDeclined => MsgToPlayer::DrawOfferDeclined,
+            Self::YouWon(x) => MsgToPlayer::YouWon(x),
+            Self::YouLose(x) => MsgToPlayer::YouLose(x),
+            Self::PlayerWon(a, b, c) => MsgToPlayer::PlayerWon(a, b, c),
+            Self::Draw(x) => MsgToPlayer::Draw(x),
+            Self::RatingChanged(x) => MsgToPlayer::RatingChanged(x),
+            Self::Leaderboard(x) => MsgToPlayer::Leaderboard(x),
+            Self::Score(x) => MsgToPlayer::Score(x),
+            Self::YouWonMatch => MsgToPlayer::YouWonMatch,
+            Self::YouLostMatch => MsgToPlayer::YouLostMatch,
+            Self::MatchDrawn => MsgToPlayer::MatchDrawn,
+            Self::RematchQuestion => MsgToPlayer::RematchQuestion,
+            Self::WaitingForRematchAnswer => MsgToPlayer::WaitingForRematchAnswer,
+            Self::OtherPlayerWantsRematch => MsgToPlayer::OtherPlayerWantsRematch,
+            Self::OtherPlayerDeclinedRematch => MsgToPlayer::OtherPlayerDeclinedRematch,
+            Self::Goodbye => MsgToPlayer::Goodbye,
+            Self::Playboard(x) => MsgToPlayer::Playboard(f(x)),
+        }
+    }
+}
+
+/// Authentication command of client of structured protocol - JSON object with "type" field
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthCommand {
+    Login { name: String, password: String },
+    Register { name: String, password: String },
+    Guest,
+}
+
+impl AuthCommand {
+    /// Check credentials and return name of account (None for guest)
+    pub async fn authenticate(
+        self,
+        accounts: &super::accounts::AccountStore,
+    ) -> Result<Option<String>, super::accounts::AccountError> {
+        match self {
+            Self::Login { name, password } => {
+                accounts.login(&name, &password).await.map(|_| Some(name))
+            }
+            Self::Register { name, password } => accounts
+                .register(&name, &password)
+                .await
+                .map(|_| Some(name)),
+            Self::Guest => Ok(None),
+        }
+    }
+}
 
 /// Trait for player struct
 #[async_trait::async_trait]
 pub trait PlayerTrait {
     type FieldRepresentation;
 
-    /// Get player id
-    fn get_player_id(&self) -> super::PlayerId;
+    /// Get role of player in game
+    fn get_role(&self) -> Role;
+
+    /// Set new role (e.g. after swap of sides)
+    fn set_role(&mut self, role: Role);
+
+    /// Get nickname chosen by player
+    fn get_nickname(&self) -> Option<&str>;
+
+    /// Set nickname of player
+    fn set_nickname(&mut self, nickname: String);
+
+    /// Get name of account of authenticated player
+    fn get_account(&self) -> Option<&str>;
+
+    /// Get id of client connection
+    fn get_client_id(&self) -> ClientId;
 
     /// Send new message to player
     async fn send_msg_to_player(&mut self, msg: MsgToPlayer<Self::FieldRepresentation>);
@@ -46,11 +311,11 @@ pub trait PlayerTrait {
 /// Possible messages sended from player
 pub enum MsgFromPlayer<T, O> {
     /// New player joined
-    Join(T),
+    Join(ClientId, T),
     /// Message from player
-    Msg(super::PlayerId, O),
+    Msg(ClientId, O),
     /// Player leave
-    Leave(super::PlayerId),
+    Leave(ClientId),
 }
 
 /// Trait for player manager struct
@@ -66,7 +331,8 @@ pub trait PlayerManagerTrait {
     /// Creating new player from player_data
     fn create_new_player(
         &self,
-        player_id: super::PlayerId,
+        client_id: ClientId,
+        role: Role,
         player_data: Self::NewPlayerData,
     ) -> Self::NewPlayer;
 
@@ -0,0 +1,379 @@
+use super::{ClientId, MsgFromPlayer, PlayerManagerTrait, PlayerTrait, Role};
+
+/// Player connected through any of player managers joined in composite
+pub struct Player<F> {
+    player: Box<dyn PlayerTrait<FieldRepresentation = F> + Send + Sync>,
+    /// Id of client in composite - wrapped player knows only id of its player manager
+    client_id: ClientId,
+}
+
+#[async_trait::async_trait]
+impl<F> PlayerTrait for Player<F>
+where
+    F: Send + 'static,
+{
+    type FieldRepresentation = F;
+
+    async fn send_msg_to_player(&mut self, msg: super::MsgToPlayer<Self::FieldRepresentation>) {
+        self.player.send_msg_to_player(msg).await;
+    }
+
+    fn get_role(&self) -> Role {
+        self.player.get_role()
+    }
+
+    fn set_role(&mut self, role: Role) {
+        self.player.set_role(role);
+    }
+
+    fn get_nickname(&self) -> Option<&str> {
+        self.player.get_nickname()
+    }
+
+    fn set_nickname(&mut self, nickname: String) {
+        self.player.set_nickname(nickname);
+    }
+
+    fn get_account(&self) -> Option<&str> {
+        self.player.get_account()
+    }
+
+    fn get_client_id(&self) -> ClientId {
+        self.client_id
+    }
+}
+
+/// Player of joined player manager getting playboard in representation of its player manager
+struct ConvertingPlayer<P, F> {
+    player: P,
+    /// Representation of playboard used by composite
+    representation: std::marker::PhantomData<fn(F)>,
+}
+
+#[async_trait::async_trait]
+impl<P, F> PlayerTrait for ConvertingPlayer<P, F>
+where
+    P: PlayerTrait + Send,
+    P::FieldRepresentation: Send,
+    F: Into<P::FieldRepresentation> + Send + 'static,
+{
+    type FieldRepresentation = F;
+
+    async fn send_msg_to_player(&mut self, msg: super::MsgToPlayer<Self::FieldRepresentation>) {
+        self.player
+            .send_msg_to_player(msg.map_playboard(Into::into))
+            .await;
+    }
+
+    fn get_role(&self) -> Role {
+        self.player.get_role()
+    }
+
+    fn set_role(&mut self, role: Role) {
+        self.player.set_role(role);
+    }
+
+    fn get_nickname(&self) -> Option<&str> {
+        self.player.get_nickname()
+    }
+
+    fn set_nickname(&mut self, nickname: String) {
+        self.player.set_nickname(nickname);
+    }
+
+    fn get_account(&self) -> Option<&str> {
+        self.player.get_account()
+    }
+
+    fn get_client_id(&self) -> ClientId {
+        self.player.get_client_id()
+    }
+}
+
+/// Player manager multiplexing messages of several player managers (e.g. TCP and WebSocket
+/// clients playing same game)
+pub struct PlayerManager<F, M> {
+    tx: tokio::sync::mpsc::Sender<MsgFromPlayer<Player<F>, M>>,
+    rx: tokio::sync::mpsc::Receiver<MsgFromPlayer<Player<F>, M>>,
+    /// Ids of clients are unique across all joined player managers
+    next_client_id: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+/// Create players of joined player manager and pass its messages to composite under ids of
+/// clients in composite
+async fn forward_messages<T, F, M>(
+    mut player_manager: T,
+    tx: tokio::sync::mpsc::Sender<MsgFromPlayer<Player<F>, M>>,
+    next_client_id: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+) where
+    T: PlayerManagerTrait<PlayerMsg = M>,
+    T::NewPlayer: Send + Sync + 'static,
+    <T::NewPlayer as PlayerTrait>::FieldRepresentation: Send,
+    F: Into<<T::NewPlayer as PlayerTrait>::FieldRepresentation> + Send + 'static,
+{
+    let mut client_ids = std::collections::HashMap::new();
+
+    loop {
+        let msg = match player_manager.receive_new_message().await {
+            MsgFromPlayer::Join(client_id, player_data) => {
+                let id =
+                    ClientId(next_client_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+                client_ids.insert(client_id, id);
+
+                // Role is set by game when player is created by composite
+                let player = Player {
+                    player: Box::new(ConvertingPlayer {
+                        player: player_manager.create_new_player(
+                            client_id,
+                            Role::Guest,
+                            player_data,
+                        ),
+                        representation: std::marker::PhantomData,
+                    }),
+                    client_id: id,
+                };
+                MsgFromPlayer::Join(id, player)
+            }
+            MsgFromPlayer::Msg(client_id, msg) => match client_ids.get(&client_id) {
+                Some(id) => MsgFromPlayer::Msg(*id, msg),
+                None => continue,
+            },
+            MsgFromPlayer::Leave(client_id) => match client_ids.remove(&client_id) {
+                Some(id) => MsgFromPlayer::Leave(id),
+                None => continue,
+            },
+        };
+
+        if tx.send(msg).await.is_err() {
+            return;
+        }
+    }
+}
+
+impl<F, M> PlayerManager<F, M>
+where
+    F: Send + 'static,
+    M: Send + 'static,
+{
+    pub fn new() -> Self {
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
+
+        PlayerManager {
+            tx,
+            rx,
+            next_client_id: std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+        }
+    }
+
+    /// Join clients of other player manager - playboard is converted to representation of its
+    /// players
+    pub fn add<T>(&mut self, player_manager: T)
+    where
+        T: PlayerManagerTrait<PlayerMsg = M> + Send + 'static,
+        T::NewPlayer: Send + Sync + 'static,
+        <T::NewPlayer as PlayerTrait>::FieldRepresentation: Send,
+        F: Into<<T::NewPlayer as PlayerTrait>::FieldRepresentation>,
+        T::NewPlayerData: Send,
+    {
+        tokio::spawn(forward_messages(
+            player_manager,
+            self.tx.clone(),
+            self.next_client_id.clone(),
+        ));
+    }
+}
+
+#[async_trait::async_trait]
+impl<F, M> PlayerManagerTrait for PlayerManager<F, M>
+where
+    F: Send + 'static,
+    M: Send,
+{
+    type NewPlayerData = Player<F>;
+    type NewPlayer = Player<F>;
+    type PlayerMsg = M;
+
+    fn create_new_player(
+        &self,
+        _client_id: ClientId,
+        role: Role,
+        mut player_data: Self::NewPlayerData,
+    ) -> Self::NewPlayer {
+        player_data.set_role(role);
+        player_data
+    }
+
+    async fn receive_new_message(&mut self) -> MsgFromPlayer<Self::NewPlayerData, Self::PlayerMsg> {
+        // Composite holds own sender, so channel is never closed
+        self.rx.recv().await.unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::MsgToPlayer;
+    use super::*;
+
+    /// Messages sent to players of wrapped player manager
+    type SentMessages<F> = tokio::sync::mpsc::UnboundedReceiver<(ClientId, MsgToPlayer<F>)>;
+
+    /// Player of wrapped player manager reporting messages to channel
+    struct ChannelPlayer<F> {
+        role: Role,
+        client_id: ClientId,
+        tx: tokio::sync::mpsc::UnboundedSender<(ClientId, MsgToPlayer<F>)>,
+    }
+
+    #[async_trait::async_trait]
+    impl<F> PlayerTrait for ChannelPlayer<F>
+    where
+        F: Send + 'static,
+    {
+        type FieldRepresentation = F;
+
+        async fn send_msg_to_player(&mut self, msg: MsgToPlayer<Self::FieldRepresentation>) {
+            let _ = self.tx.send((self.client_id, msg));
+        }
+
+        fn get_role(&self) -> Role {
+            self.role
+        }
+
+        fn set_role(&mut self, role: Role) {
+            self.role = role;
+        }
+
+        fn get_nickname(&self) -> Option<&str> {
+            None
+        }
+
+        fn set_nickname(&mut self, _nickname: String) {}
+
+        fn get_account(&self) -> Option<&str> {
+            None
+        }
+
+        fn get_client_id(&self) -> ClientId {
+            self.client_id
+        }
+    }
+
+    /// Player manager receiving messages of clients from channel
+    struct ChannelPlayerManager<F> {
+        rx: tokio::sync::mpsc::UnboundedReceiver<MsgFromPlayer<(), String>>,
+        tx_player: tokio::sync::mpsc::UnboundedSender<(ClientId, MsgToPlayer<F>)>,
+    }
+
+    #[async_trait::async_trait]
+    impl<F> PlayerManagerTrait for ChannelPlayerManager<F>
+    where
+        F: Send + 'static,
+    {
+        type NewPlayerData = ();
+        type NewPlayer = ChannelPlayer<F>;
+        type PlayerMsg = String;
+
+        fn create_new_player(
+            &self,
+            client_id: ClientId,
+            role: Role,
+            _player_data: Self::NewPlayerData,
+        ) -> Self::NewPlayer {
+            ChannelPlayer {
+                role,
+                client_id,
+                tx: self.tx_player.clone(),
+            }
+        }
+
+        async fn receive_new_message(
+            &mut self,
+        ) -> MsgFromPlayer<Self::NewPlayerData, Self::PlayerMsg> {
+            match self.rx.recv().await {
+                Some(x) => x,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    fn channel_player_manager<F>() -> (
+        ChannelPlayerManager<F>,
+        tokio::sync::mpsc::UnboundedSender<MsgFromPlayer<(), String>>,
+        SentMessages<F>,
+    ) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_player, rx_player) = tokio::sync::mpsc::unbounded_channel();
+
+        (ChannelPlayerManager { rx, tx_player }, tx, rx_player)
+    }
+
+    #[tokio::test]
+    async fn clients_of_managers_get_unique_ids() {
+        let (first, tx_first, mut rx_first) = channel_player_manager::<()>();
+        let (second, tx_second, _rx_second) = channel_player_manager::<()>();
+
+        let mut composite = PlayerManager::<(), String>::new();
+        composite.add(first);
+        composite.add(second);
+
+        // Both wrapped managers use id 0 for their first client
+        let _ = tx_first.send(MsgFromPlayer::Join(ClientId(0), ()));
+        let mut player = match composite.receive_new_message().await {
+            MsgFromPlayer::Join(ClientId(0), x) => x,
+            _ => panic!("Client of first manager did not join"),
+        };
+        let _ = tx_second.send(MsgFromPlayer::Join(ClientId(0), ()));
+        assert!(matches!(
+            composite.receive_new_message().await,
+            MsgFromPlayer::Join(ClientId(1), _)
+        ));
+
+        // Messages of clients are passed under ids in composite
+        let _ = tx_second.send(MsgFromPlayer::Msg(ClientId(0), "1-1".to_owned()));
+        assert!(matches!(
+            composite.receive_new_message().await,
+            MsgFromPlayer::Msg(ClientId(1), msg) if msg == "1-1"
+        ));
+
+        // Unknown clients are ignored
+        let _ = tx_first.send(MsgFromPlayer::Msg(ClientId(5), "1-1".to_owned()));
+        let _ = tx_first.send(MsgFromPlayer::Leave(ClientId(0)));
+        assert!(matches!(
+            composite.receive_new_message().await,
+            MsgFromPlayer::Leave(ClientId(0))
+        ));
+
+        // Player created by composite gets role and sends messages through wrapped player
+        player = composite.create_new_player(ClientId(0), Role::Spectator, player);
+        assert_eq!(player.get_role(), Role::Spectator);
+        assert_eq!(player.get_client_id(), ClientId(0));
+
+        player
+            .send_msg_to_player(MsgToPlayer::WelcomeSpectator)
+            .await;
+        assert!(matches!(
+            rx_first.recv().await,
+            Some((ClientId(0), MsgToPlayer::WelcomeSpectator))
+        ));
+    }
+
+    #[tokio::test]
+    async fn playboard_is_converted_for_wrapped_player() {
+        let (wrapped, tx, mut rx) = channel_player_manager::<u16>();
+
+        let mut composite = PlayerManager::<u8, String>::new();
+        composite.add(wrapped);
+
+        let _ = tx.send(MsgFromPlayer::Join(ClientId(0), ()));
+        let mut player = match composite.receive_new_message().await {
+            MsgFromPlayer::Join(ClientId(0), x) => x,
+            _ => panic!("Client did not join"),
+        };
+
+        player.send_msg_to_player(MsgToPlayer::Playboard(7)).await;
+        assert!(matches!(
+            rx.recv().await,
+            Some((ClientId(0), MsgToPlayer::Playboard(7u16)))
+        ));
+    }
+}
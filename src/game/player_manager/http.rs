@@ -0,0 +1,584 @@
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+
+use super::{AuthCommand, ClientId, MsgFromPlayer, Role};
+
+/// Time after which session of client without any request is closed
+const SESSION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+/// Default and maximal time of waiting for events in long-polling request - polling has to end
+/// well before session timeout
+const DEFAULT_POLL_TIMEOUT: u64 = 20;
+const MAX_POLL_TIMEOUT: u64 = 30;
+/// Maximal number of messages waiting for polling request - oldest messages are dropped
+const MAX_EVENTS: usize = 256;
+
+/// State of client connected through HTTP requests
+struct Session {
+    client_id: ClientId,
+    /// Messages waiting for next polling request
+    events: std::sync::Mutex<std::collections::VecDeque<serde_json::Value>>,
+    /// Last playboard sended to client
+    board: std::sync::Mutex<Option<super::super::converters::Board>>,
+    notify: tokio::sync::Notify,
+    /// Player was dropped by game
+    closed: std::sync::atomic::AtomicBool,
+    last_request: std::sync::Mutex<tokio::time::Instant>,
+}
+
+impl Session {
+    fn new(client_id: ClientId) -> Self {
+        Session {
+            client_id,
+            events: std::sync::Mutex::new(std::collections::VecDeque::new()),
+            board: std::sync::Mutex::new(None),
+            notify: tokio::sync::Notify::new(),
+            closed: std::sync::atomic::AtomicBool::new(false),
+            last_request: std::sync::Mutex::new(tokio::time::Instant::now()),
+        }
+    }
+
+    /// Queue message for next polling request
+    fn push_event(&self, event: serde_json::Value) {
+        let mut events = self.events.lock().unwrap();
+
+        if events.len() == MAX_EVENTS {
+            log::warn!(
+                "HTTP client {} does not poll messages, oldest message is dropped",
+                self.client_id
+            );
+            events.pop_front();
+        }
+        events.push_back(event);
+        drop(events);
+
+        self.notify.notify_one();
+    }
+
+    fn touch(&self) {
+        *self.last_request.lock().unwrap() = tokio::time::Instant::now();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+/// Joined client
+pub struct Connection {
+    session: std::sync::Arc<Session>,
+    /// Name of account of logged in client
+    account: Option<String>,
+}
+
+pub struct Player {
+    role: Role,
+    nickname: Option<String>,
+    /// Name of account of logged in client
+    account: Option<String>,
+    session: std::sync::Arc<Session>,
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.session
+            .closed
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        self.session.notify.notify_one();
+    }
+}
+
+#[async_trait::async_trait]
+impl super::PlayerTrait for Player {
+    type FieldRepresentation = super::super::converters::Board;
+
+    async fn send_msg_to_player(&mut self, msg: super::MsgToPlayer<Self::FieldRepresentation>) {
+        if let super::MsgToPlayer::Playboard(playboard) = &msg {
+            *self.session.board.lock().unwrap() = Some(playboard.clone())
+        }
+
+        match serde_json::to_value(&msg) {
+            Ok(event) => self.session.push_event(event),
+            Err(err) => log::error!(
+                "Unable to encode message for {}: {}",
+                self.session.client_id,
+                err
+            ),
+        }
+    }
+
+    fn get_role(&self) -> Role {
+        self.role
+    }
+
+    fn set_role(&mut self, role: Role) {
+        self.role = role;
+    }
+
+    fn get_nickname(&self) -> Option<&str> {
+        self.nickname.as_deref()
+    }
+
+    fn set_nickname(&mut self, nickname: String) {
+        self.nickname = Some(nickname);
+    }
+
+    fn get_account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    fn get_client_id(&self) -> ClientId {
+        self.session.client_id
+    }
+}
+
+/// State shared by request handlers
+struct Server {
+    /// Sessions by tokens of clients
+    sessions: std::sync::Mutex<std::collections::HashMap<String, std::sync::Arc<Session>>>,
+    next_client_id: std::sync::atomic::AtomicUsize,
+    accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    tx: tokio::sync::mpsc::Sender<MsgFromPlayer<Connection, String>>,
+}
+
+#[derive(serde::Deserialize)]
+struct TokenQuery {
+    token: String,
+}
+
+#[derive(serde::Deserialize)]
+struct EventsQuery {
+    token: String,
+    /// Time of waiting for first event in seconds
+    timeout: Option<u64>,
+}
+
+#[derive(serde::Deserialize)]
+struct MoveRequest {
+    row: usize,
+    column: usize,
+}
+
+fn error_response(status: StatusCode, error: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": error }))).into_response()
+}
+
+impl Server {
+    /// Find open session of token
+    fn get_session(&self, token: &str) -> Result<std::sync::Arc<Session>, (StatusCode, &str)> {
+        match self.sessions.lock().unwrap().get(token) {
+            Some(session) if session.is_closed() => Err((StatusCode::GONE, "session is closed")),
+            Some(session) => {
+                session.touch();
+                Ok(session.clone())
+            }
+            None => Err((StatusCode::NOT_FOUND, "unknown token")),
+        }
+    }
+
+    /// Close sessions of clients which stopped sending requests
+    async fn remove_inactive_sessions(&self) {
+        let now = tokio::time::Instant::now();
+        let mut left = Vec::new();
+
+        self.sessions.lock().unwrap().retain(|_, session| {
+            match now.duration_since(*session.last_request.lock().unwrap()) < SESSION_TIMEOUT {
+                true => true,
+                false => {
+                    if !session.is_closed() {
+                        left.push(session.client_id);
+                    }
+                    false
+                }
+            }
+        });
+
+        for client_id in left {
+            log::info!("HTTP client {} timed out", client_id);
+            let _ = self.tx.send(MsgFromPlayer::Leave(client_id)).await;
+        }
+    }
+}
+
+/// Join game - body may contain credentials if accounts are enabled
+async fn join(State(server): State<std::sync::Arc<Server>>, body: String) -> Response {
+    let account = match &server.accounts {
+        Some(accounts) => {
+            let command: AuthCommand = match serde_json::from_str(&body) {
+                Ok(x) => x,
+                Err(_) => return error_response(StatusCode::BAD_REQUEST, "invalid credentials"),
+            };
+
+            match command.authenticate(accounts).await {
+                Ok(x) => x,
+                Err(err) => return error_response(StatusCode::UNAUTHORIZED, &err.to_string()),
+            }
+        }
+        None => None,
+    };
+
+    let client_id = ClientId(
+        server
+            .next_client_id
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+    );
+    let token = super::super::generate_session_token();
+
+    match &account {
+        Some(name) => log::info!("New HTTP client {} logged in as {}", client_id, name),
+        None => log::info!("New HTTP client {}", client_id),
+    }
+
+    let session = std::sync::Arc::new(Session::new(client_id));
+    server
+        .sessions
+        .lock()
+        .unwrap()
+        .insert(token.clone(), session.clone());
+
+    let connection = Connection { session, account };
+    let _ = server
+        .tx
+        .send(MsgFromPlayer::Join(client_id, connection))
+        .await;
+
+    Json(serde_json::json!({ "token": token })).into_response()
+}
+
+/// Send command of structured protocol to game
+async fn command(
+    State(server): State<std::sync::Arc<Server>>,
+    Query(query): Query<TokenQuery>,
+    body: String,
+) -> Response {
+    let session = match server.get_session(&query.token) {
+        Ok(x) => x,
+        Err((status, error)) => return error_response(status, error),
+    };
+
+    let _ = server
+        .tx
+        .send(MsgFromPlayer::Msg(session.client_id, body))
+        .await;
+
+    StatusCode::ACCEPTED.into_response()
+}
+
+/// Make move on position indexed from 1
+async fn make_move(
+    State(server): State<std::sync::Arc<Server>>,
+    Query(query): Query<TokenQuery>,
+    Json(request): Json<MoveRequest>,
+) -> Response {
+    let session = match server.get_session(&query.token) {
+        Ok(x) => x,
+        Err((status, error)) => return error_response(status, error),
+    };
+
+    let command = serde_json::json!({
+        "type": "move",
+        "row": request.row,
+        "column": request.column,
+    });
+    let _ = server
+        .tx
+        .send(MsgFromPlayer::Msg(session.client_id, command.to_string()))
+        .await;
+
+    StatusCode::ACCEPTED.into_response()
+}
+
+/// Last playboard sended to client
+async fn board(
+    State(server): State<std::sync::Arc<Server>>,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    match server.get_session(&query.token) {
+        Ok(session) => Json(session.board.lock().unwrap().clone()).into_response(),
+        Err((status, error)) => error_response(status, error),
+    }
+}
+
+/// Wait for messages of game (long-polling)
+async fn events(
+    State(server): State<std::sync::Arc<Server>>,
+    Query(query): Query<EventsQuery>,
+) -> Response {
+    let session = match server.sessions.lock().unwrap().get(&query.token) {
+        Some(x) => x.clone(),
+        None => return error_response(StatusCode::NOT_FOUND, "unknown token"),
+    };
+    session.touch();
+
+    let timeout = query
+        .timeout
+        .unwrap_or(DEFAULT_POLL_TIMEOUT)
+        .min(MAX_POLL_TIMEOUT);
+
+    // Notification may be left from messages taken by previous request
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(timeout);
+    while session.events.lock().unwrap().is_empty() && !session.is_closed() {
+        if tokio::time::timeout_at(deadline, session.notify.notified())
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+
+    // Client is active during whole polling, not only at its start
+    session.touch();
+    let events: Vec<serde_json::Value> = session.events.lock().unwrap().drain(..).collect();
+
+    // Remaining messages are delivered before closed session is removed
+    if events.is_empty() && session.is_closed() {
+        server.sessions.lock().unwrap().remove(&query.token);
+        return error_response(StatusCode::GONE, "session is closed");
+    }
+
+    Json(events).into_response()
+}
+
+/// Leave game
+async fn leave(
+    State(server): State<std::sync::Arc<Server>>,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    let session = match server.sessions.lock().unwrap().remove(&query.token) {
+        Some(x) => x,
+        None => return error_response(StatusCode::NOT_FOUND, "unknown token"),
+    };
+
+    if !session.is_closed() {
+        let _ = server
+            .tx
+            .send(MsgFromPlayer::Leave(session.client_id))
+            .await;
+    }
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+async fn session_cleaner(server: std::sync::Arc<Server>) {
+    let mut interval = tokio::time::interval(SESSION_TIMEOUT / 4);
+
+    loop {
+        interval.tick().await;
+        server.remove_inactive_sessions().await;
+    }
+}
+
+pub struct PlayerManager {
+    rx: tokio::sync::mpsc::Receiver<MsgFromPlayer<Connection, String>>,
+}
+
+impl PlayerManager {
+    pub fn new(
+        listener: tokio::net::TcpListener,
+        accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    ) -> Self {
+        log::info!(
+            "HTTP player manager running on address: {}",
+            listener.local_addr().unwrap()
+        );
+        super::tcp::log_connection_hints(&listener);
+
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
+
+        let server = std::sync::Arc::new(Server {
+            sessions: std::sync::Mutex::new(std::collections::HashMap::new()),
+            next_client_id: std::sync::atomic::AtomicUsize::new(0),
+            accounts,
+            tx,
+        });
+
+        let app = axum::Router::new()
+            .route("/join", axum::routing::post(join))
+            .route("/move", axum::routing::post(make_move))
+            .route("/command", axum::routing::post(command))
+            .route("/board", axum::routing::get(board))
+            .route("/events", axum::routing::get(events))
+            .route("/leave", axum::routing::post(leave))
+            .with_state(server.clone());
+
+        tokio::spawn(session_cleaner(server));
+        tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, app).await {
+                log::error!("HTTP server failed: {}", err);
+            }
+        });
+
+        PlayerManager { rx }
+    }
+
+    pub async fn from_socket_address(
+        addr: std::net::SocketAddr,
+        accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    ) -> Result<PlayerManager, std::io::Error> {
+        let listener = super::tcp::bind_listener(addr)?;
+        Ok(Self::new(listener, accounts))
+    }
+}
+
+#[async_trait::async_trait]
+impl super::PlayerManagerTrait for PlayerManager {
+    type NewPlayerData = Connection;
+    type NewPlayer = Player;
+    type PlayerMsg = String;
+
+    fn create_new_player(
+        &self,
+        _client_id: ClientId,
+        role: Role,
+        player_data: Self::NewPlayerData,
+    ) -> Self::NewPlayer {
+        Player {
+            role,
+            nickname: None,
+            account: player_data.account,
+            session: player_data.session,
+        }
+    }
+
+    async fn receive_new_message(&mut self) -> MsgFromPlayer<Self::NewPlayerData, Self::PlayerMsg> {
+        return self.rx.recv().await.unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{MsgToPlayer, PlayerManagerTrait, PlayerTrait};
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Send request and get status and body of response
+    async fn request(
+        addr: std::net::SocketAddr,
+        method: &str,
+        path: &str,
+        body: &str,
+    ) -> (u16, String) {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, body.to_owned())
+    }
+
+    #[tokio::test]
+    async fn session_of_client() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut player_manager = PlayerManager::new(listener, None);
+
+        let (status, body) = request(addr, "POST", "/join", "").await;
+        assert_eq!(status, 200);
+        let token = serde_json::from_str::<serde_json::Value>(&body).unwrap()["token"]
+            .as_str()
+            .unwrap()
+            .to_owned();
+
+        let connection = match player_manager.receive_new_message().await {
+            MsgFromPlayer::Join(ClientId(0), x) => x,
+            _ => panic!("Client did not join"),
+        };
+        let mut player = player_manager.create_new_player(ClientId(0), Role::Guest, connection);
+        player.send_msg_to_player(MsgToPlayer::WelcomePlayer).await;
+        player
+            .send_msg_to_player(MsgToPlayer::Playboard(vec![vec![
+                Some(super::super::super::PlayerId::Circle),
+                None,
+            ]]))
+            .await;
+
+        let (status, body) = request(addr, "GET", &format!("/events?token={}", token), "").await;
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            r#"[{"type":"welcome_player"},{"data":[["o",null]],"type":"playboard"}]"#
+        );
+        let (_, body) = request(addr, "GET", &format!("/board?token={}", token), "").await;
+        assert_eq!(body, r#"[["o",null]]"#);
+
+        let path = format!("/move?token={}", token);
+        let (status, _) = request(addr, "POST", &path, r#"{"row":1,"column":2}"#).await;
+        assert_eq!(status, 202);
+        assert!(matches!(
+            player_manager.receive_new_message().await,
+            MsgFromPlayer::Msg(ClientId(0), msg) if msg == r#"{"column":2,"row":1,"type":"move"}"#
+        ));
+
+        let (status, _) = request(addr, "POST", &format!("/leave?token={}", token), "").await;
+        assert_eq!(status, 204);
+        assert!(matches!(
+            player_manager.receive_new_message().await,
+            MsgFromPlayer::Leave(ClientId(0))
+        ));
+        let (status, _) = request(addr, "GET", &format!("/events?token={}", token), "").await;
+        assert_eq!(status, 404);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn polling_client_is_not_timed_out() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        let server = std::sync::Arc::new(Server {
+            sessions: std::sync::Mutex::new(std::collections::HashMap::new()),
+            next_client_id: std::sync::atomic::AtomicUsize::new(1),
+            accounts: None,
+            tx,
+        });
+        let session = std::sync::Arc::new(Session::new(ClientId(0)));
+        server
+            .sessions
+            .lock()
+            .unwrap()
+            .insert("token".to_owned(), session);
+        tokio::spawn(session_cleaner(server.clone()));
+
+        // Polls of maximal length follow each other
+        for _ in 0..10 {
+            let query = EventsQuery {
+                token: "token".to_owned(),
+                timeout: Some(MAX_POLL_TIMEOUT),
+            };
+            let response = events(State(server.clone()), Query(query)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Round trip of client before next poll
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            assert!(rx.try_recv().is_err());
+        }
+
+        // Client which stopped polling is timed out
+        tokio::time::sleep(SESSION_TIMEOUT * 2).await;
+        assert!(matches!(
+            rx.recv().await,
+            Some(MsgFromPlayer::Leave(ClientId(0)))
+        ));
+    }
+
+    #[test]
+    fn oldest_events_are_dropped() {
+        let session = Session::new(ClientId(0));
+
+        for index in 0..MAX_EVENTS + 1 {
+            session.push_event(serde_json::json!(index));
+        }
+
+        let events = session.events.lock().unwrap();
+        assert_eq!(events.len(), MAX_EVENTS);
+        assert_eq!(events[0], serde_json::json!(1));
+    }
+}
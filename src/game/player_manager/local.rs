@@ -0,0 +1,285 @@
+use std::io::Write;
+use tokio::io::AsyncBufReadExt;
+
+use super::{ClientId, MsgFromPlayer, Role};
+
+/// Number of players sitting at terminal
+const SEATS: usize = 2;
+
+/// Input and output of terminal shared by players
+#[derive(Default)]
+struct TerminalState {
+    /// Players waiting for input with their prompts - line typed by user belongs to first of
+    /// them, prompts of others are shown after it
+    prompts: std::collections::VecDeque<(ClientId, String)>,
+    /// Lines typed when nobody was asked for input - they belong to next prompts
+    typed_ahead: std::collections::VecDeque<String>,
+}
+
+type Terminal = std::sync::Arc<std::sync::Mutex<TerminalState>>;
+
+/// Last text printed to terminal is prompt waiting for input on same line
+static PROMPT_SHOWN: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+fn print(text: &str) {
+    let text = text.replace("\r\n", "\n");
+    let text = match PROMPT_SHOWN.swap(!text.ends_with('\n'), std::sync::atomic::Ordering::Relaxed)
+    {
+        true => format!("\n{}", text),
+        false => text,
+    };
+
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(text.as_bytes());
+    let _ = stdout.flush();
+}
+
+pub struct Player {
+    role: Role,
+    nickname: Option<String>,
+    client_id: ClientId,
+    terminal: Terminal,
+    tx: tokio::sync::mpsc::UnboundedSender<MsgFromPlayer<(), String>>,
+    /// Notified when player leaves terminal - nobody else can take the seat
+    finished: std::sync::Arc<tokio::sync::Notify>,
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.terminal
+            .lock()
+            .unwrap()
+            .prompts
+            .retain(|(x, _)| *x != self.client_id);
+
+        self.finished.notify_one();
+    }
+}
+
+/// Message asks player for input
+fn is_prompt<T>(msg: &super::MsgToPlayer<T>) -> bool {
+    matches!(
+        msg,
+        super::MsgToPlayer::EnterNickname
+            | super::MsgToPlayer::InvalidNickname(_)
+            | super::MsgToPlayer::EnterSessionToken
+            | super::MsgToPlayer::InvalidSessionToken
+            | super::MsgToPlayer::YourAreOnMove
+            | super::MsgToPlayer::InvalidInput
+            | super::MsgToPlayer::AlreadyTaken
+            | super::MsgToPlayer::SwapNotAvailable
+            | super::MsgToPlayer::DrawOffered
+            | super::MsgToPlayer::RematchQuestion
+    )
+}
+
+#[async_trait::async_trait]
+impl super::PlayerTrait for Player {
+    type FieldRepresentation = super::super::converters::RenderedPlayboard;
+
+    async fn send_msg_to_player(&mut self, msg: super::MsgToPlayer<Self::FieldRepresentation>) {
+        // Both players see same playboard and know who is on move
+        match (&msg, self.role) {
+            (super::MsgToPlayer::Playboard(_), Role::Player(super::super::PlayerId::Cross))
+            | (super::MsgToPlayer::OtherPlayerIsOnMove(_), _) => return,
+            _ => (),
+        }
+
+        let text = super::tcp::msg_to_text(&msg, self.role, false, false);
+        // Side of player is known after seating, so players are distinguished by seats before it
+        let text = match (&msg, &self.nickname) {
+            (super::MsgToPlayer::Playboard(_), _) => text,
+            (_, Some(nickname)) => format!("[{} ({})] {}", nickname, self.role, text),
+            (_, None) => format!("[player {}] {}", self.client_id.0 + 1, text),
+        };
+
+        if !is_prompt(&msg) {
+            return print(&text);
+        }
+
+        // Prompt is shown when player is first waiting for input
+        let mut terminal = self.terminal.lock().unwrap();
+        match terminal
+            .prompts
+            .iter()
+            .position(|(x, _)| *x == self.client_id)
+        {
+            Some(index) => terminal.prompts[index].1 = text.clone(),
+            None => terminal.prompts.push_back((self.client_id, text.clone())),
+        }
+        if terminal.prompts.front().map(|(x, _)| *x) != Some(self.client_id) {
+            return;
+        }
+
+        // Line typed ahead answers prompt at once
+        match terminal.typed_ahead.pop_front() {
+            Some(line) => {
+                terminal.prompts.pop_front();
+                print(&format!("{}{}\n", text, line));
+                let _ = self.tx.send(MsgFromPlayer::Msg(self.client_id, line));
+            }
+            None => print(&text),
+        }
+    }
+
+    fn get_role(&self) -> Role {
+        self.role
+    }
+
+    fn set_role(&mut self, role: Role) {
+        self.role = role;
+    }
+
+    fn get_nickname(&self) -> Option<&str> {
+        self.nickname.as_deref()
+    }
+
+    fn set_nickname(&mut self, nickname: String) {
+        self.nickname = Some(nickname);
+    }
+
+    fn get_account(&self) -> Option<&str> {
+        None
+    }
+
+    fn get_client_id(&self) -> ClientId {
+        self.client_id
+    }
+}
+
+/// Player manager of two players taking turns at one terminal
+pub struct PlayerManager {
+    tx: tokio::sync::mpsc::UnboundedSender<MsgFromPlayer<(), String>>,
+    rx: tokio::sync::mpsc::UnboundedReceiver<MsgFromPlayer<(), String>>,
+    terminal: Terminal,
+    finished: std::sync::Arc<tokio::sync::Notify>,
+}
+
+/// Pass lines typed by user to players asking for input
+async fn terminal_reader(
+    terminal: Terminal,
+    tx: tokio::sync::mpsc::UnboundedSender<MsgFromPlayer<(), String>>,
+) {
+    let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => {
+                // Input of user ended line with prompt
+                PROMPT_SHOWN.store(false, std::sync::atomic::Ordering::Relaxed);
+
+                let mut terminal = terminal.lock().unwrap();
+                match terminal.prompts.pop_front() {
+                    Some((client_id, _)) => {
+                        if let Some((_, text)) = terminal.prompts.front() {
+                            print(text);
+                        }
+                        let _ = tx.send(MsgFromPlayer::Msg(client_id, line));
+                    }
+                    // Line is kept for player asked next
+                    None => terminal.typed_ahead.push_back(line),
+                }
+            }
+            // Terminal closed - both players leave
+            Ok(None) | Err(_) => {
+                for index in 0..SEATS {
+                    let _ = tx.send(MsgFromPlayer::Leave(ClientId(index)));
+                }
+                return;
+            }
+        }
+    }
+}
+
+impl PlayerManager {
+    pub fn new() -> Self {
+        // Players send lines typed ahead from game task, so channel can not be full
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let terminal = Terminal::default();
+
+        // Players sit at terminal since start
+        for index in 0..SEATS {
+            let _ = tx.send(MsgFromPlayer::Join(ClientId(index), ()));
+        }
+
+        tokio::spawn(terminal_reader(terminal.clone(), tx.clone()));
+
+        PlayerManager {
+            tx,
+            rx,
+            terminal,
+            finished: std::sync::Arc::new(tokio::sync::Notify::new()),
+        }
+    }
+
+    /// Notification of end of play - player left terminal
+    pub fn get_finished(&self) -> std::sync::Arc<tokio::sync::Notify> {
+        self.finished.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl super::PlayerManagerTrait for PlayerManager {
+    type NewPlayerData = ();
+    type NewPlayer = Player;
+    type PlayerMsg = String;
+
+    fn create_new_player(
+        &self,
+        client_id: ClientId,
+        role: Role,
+        _player_data: Self::NewPlayerData,
+    ) -> Self::NewPlayer {
+        Player {
+            role,
+            nickname: None,
+            client_id,
+            terminal: self.terminal.clone(),
+            tx: self.tx.clone(),
+            finished: self.finished.clone(),
+        }
+    }
+
+    async fn receive_new_message(&mut self) -> MsgFromPlayer<Self::NewPlayerData, Self::PlayerMsg> {
+        // Player manager holds own sender, so channel is never closed
+        self.rx.recv().await.unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{MsgToPlayer, PlayerTrait};
+    use super::*;
+
+    #[tokio::test]
+    async fn typed_ahead_line_answers_next_prompt() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let terminal = Terminal::default();
+        terminal
+            .lock()
+            .unwrap()
+            .typed_ahead
+            .push_back("name alice".to_owned());
+
+        let mut player = Player {
+            role: Role::Player(super::super::super::PlayerId::Circle),
+            nickname: None,
+            client_id: ClientId(0),
+            terminal: terminal.clone(),
+            tx,
+            finished: std::sync::Arc::new(tokio::sync::Notify::new()),
+        };
+        player.send_msg_to_player(MsgToPlayer::EnterNickname).await;
+
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(MsgFromPlayer::Msg(ClientId(0), line)) if line == "name alice"
+        ));
+        assert!(terminal.lock().unwrap().prompts.is_empty());
+
+        // Without typed line player waits for input
+        player.send_msg_to_player(MsgToPlayer::YourAreOnMove).await;
+        assert!(rx.try_recv().is_err());
+        assert_eq!(terminal.lock().unwrap().prompts.len(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+use super::{ClientId, MsgFromPlayer, PlayerTrait, Role};
+
+/// Player returned from room
+pub struct LeftPlayer<P> {
+    pub player: P,
+    /// Last session token issued to player by game of room
+    pub session_token: Option<String>,
+}
+
+/// Player connected through other player manager and moved to room
+pub struct Player<P>
+where
+    P: PlayerTrait,
+{
+    /// Wrapped player - taken only when wrapper is dropped
+    player: Option<P>,
+    session_token: Option<String>,
+    /// Channel returning player to lobby after it left room
+    tx_left: tokio::sync::mpsc::UnboundedSender<LeftPlayer<P>>,
+}
+
+impl<P> Player<P>
+where
+    P: PlayerTrait,
+{
+    pub fn new(player: P, tx_left: tokio::sync::mpsc::UnboundedSender<LeftPlayer<P>>) -> Self {
+        Player {
+            player: Some(player),
+            session_token: None,
+            tx_left,
+        }
+    }
+
+    /// Set session token of player returning to interrupted game
+    pub fn set_session_token(&mut self, token: String) {
+        self.session_token = Some(token);
+    }
+
+    fn get(&self) -> &P {
+        self.player.as_ref().unwrap()
+    }
+
+    fn get_mut(&mut self) -> &mut P {
+        self.player.as_mut().unwrap()
+    }
+}
+
+impl<P> Drop for Player<P>
+where
+    P: PlayerTrait,
+{
+    fn drop(&mut self) {
+        if let Some(player) = self.player.take() {
+            let _ = self.tx_left.send(LeftPlayer {
+                player,
+                session_token: self.session_token.take(),
+            });
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> PlayerTrait for Player<P>
+where
+    P: PlayerTrait + Send,
+    P::FieldRepresentation: Send,
+{
+    type FieldRepresentation = P::FieldRepresentation;
+
+    async fn send_msg_to_player(&mut self, msg: super::MsgToPlayer<Self::FieldRepresentation>) {
+        if let super::MsgToPlayer::SessionToken(token) = &msg {
+            self.session_token = Some(token.clone());
+        }
+
+        self.get_mut().send_msg_to_player(msg).await;
+    }
+
+    fn get_role(&self) -> Role {
+        self.get().get_role()
+    }
+
+    fn set_role(&mut self, role: Role) {
+        self.get_mut().set_role(role);
+    }
+
+    fn get_nickname(&self) -> Option<&str> {
+        self.get().get_nickname()
+    }
+
+    fn set_nickname(&mut self, nickname: String) {
+        self.get_mut().set_nickname(nickname);
+    }
+
+    fn get_account(&self) -> Option<&str> {
+        self.get().get_account()
+    }
+
+    fn get_client_id(&self) -> ClientId {
+        self.get().get_client_id()
+    }
+}
+
+/// Player manager of one room receiving clients from lobby
+pub struct PlayerManager<P, M>
+where
+    P: PlayerTrait,
+{
+    rx: tokio::sync::mpsc::Receiver<MsgFromPlayer<Player<P>, M>>,
+}
+
+impl<P, M> PlayerManager<P, M>
+where
+    P: PlayerTrait,
+{
+    pub fn new(rx: tokio::sync::mpsc::Receiver<MsgFromPlayer<Player<P>, M>>) -> Self {
+        PlayerManager { rx }
+    }
+}
+
+#[async_trait::async_trait]
+impl<P, M> super::PlayerManagerTrait for PlayerManager<P, M>
+where
+    P: PlayerTrait + Send,
+    P::FieldRepresentation: Send,
+    M: Send,
+{
+    type NewPlayerData = Player<P>;
+    type NewPlayer = Player<P>;
+    type PlayerMsg = M;
+
+    fn create_new_player(
+        &self,
+        _client_id: ClientId,
+        role: Role,
+        mut player_data: Self::NewPlayerData,
+    ) -> Self::NewPlayer {
+        player_data.set_role(role);
+        player_data
+    }
+
+    async fn receive_new_message(&mut self) -> MsgFromPlayer<Self::NewPlayerData, Self::PlayerMsg> {
+        match self.rx.recv().await {
+            Some(msg) => msg,
+            // Room was closed by lobby - game task is going to be aborted
+            None => std::future::pending().await,
+        }
+    }
+}
@@ -1,13 +1,115 @@
 use std::io::Write;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
 
-use super::MsgFromPlayer;
+use super::{ClientId, MsgFromPlayer, Role};
 
-fn get_available_interfaces() -> Result<Vec<(String, std::net::IpAddr)>, ()> {
-    let network_interfaces = local_ip_address::list_afinet_netifas().map_err(|err| {
+mod bot;
+mod handshake;
+pub mod tls;
+
+/// Address of listening sockets
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ListenAddress {
+    pub ip: std::net::IpAddr,
+    /// Index of network interface of IPv6 link-local address (0 otherwise)
+    pub scope_id: u32,
+}
+
+impl ListenAddress {
+    /// Address on network interface with given name (link-local address gets its scope)
+    fn on_interface(name: &str, ip: std::net::IpAddr) -> Self {
+        let scope_id = match ip {
+            std::net::IpAddr::V6(x) if is_link_local(&x) => interface_index(name).unwrap_or(0),
+            _ => 0,
+        };
+
+        ListenAddress { ip, scope_id }
+    }
+
+    /// Socket address with given port
+    pub fn with_port(self, port: u16) -> std::net::SocketAddr {
+        match self.ip {
+            std::net::IpAddr::V4(ip) => std::net::SocketAddrV4::new(ip, port).into(),
+            std::net::IpAddr::V6(ip) => {
+                std::net::SocketAddrV6::new(ip, port, 0, self.scope_id).into()
+            }
+        }
+    }
+}
+
+/// Parse IP address - IPv6 link-local address requires scope as "fe80::1%eth0" (or "fe80::1%2")
+impl std::str::FromStr for ListenAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ip, scope) = match s.split_once('%') {
+            Some((ip, scope)) => (ip, Some(scope)),
+            None => (s, None),
+        };
+        let ip: std::net::IpAddr = ip.parse().map_err(|err| format!("{}", err))?;
+
+        let scope_id = match (ip, scope) {
+            (std::net::IpAddr::V6(_), Some(scope)) => match scope.parse() {
+                Ok(x) => x,
+                Err(_) => interface_index(scope)
+                    .ok_or_else(|| format!("network interface \"{}\" does not exist", scope))?,
+            },
+            (std::net::IpAddr::V4(_), Some(_)) => {
+                return Err("scope can be used only with IPv6 address".to_owned())
+            }
+            (std::net::IpAddr::V6(x), None) if is_link_local(&x) => {
+                return Err(format!(
+                    "link-local address requires scope (e.g. {}%eth0)",
+                    x
+                ))
+            }
+            _ => 0,
+        };
+
+        Ok(ListenAddress { ip, scope_id })
+    }
+}
+
+/// Network of listening sockets required by user
+pub enum BindTarget {
+    /// Given IP address
+    Address(ListenAddress),
+    /// Address of network interface with given name
+    Interface(String),
+    /// All network interfaces (both IPv6 and IPv4 if possible)
+    All,
+}
+
+/// Address is IPv6 unicast link-local (fe80::/10)
+fn is_link_local(ip: &std::net::Ipv6Addr) -> bool {
+    ip.segments()[0] & 0xffc0 == 0xfe80
+}
+
+/// Index of network interface with given name - resolver of system translates scope of
+/// link-local address
+fn interface_index(name: &str) -> Option<u32> {
+    use std::net::ToSocketAddrs;
+
+    match (format!("fe80::1%{}", name).as_str(), 0)
+        .to_socket_addrs()
+        .ok()?
+        .next()?
+    {
+        std::net::SocketAddr::V6(x) if x.scope_id() != 0 => Some(x.scope_id()),
+        _ => None,
+    }
+}
+
+#[allow(clippy::unused_unit)]
+fn load_interfaces() -> Result<Vec<(String, std::net::IpAddr)>, ()> {
+    local_ip_address::list_afinet_netifas().map_err(|err| {
         log::error!("Unable to load network interfaces: {}", err);
         ()
-    })?;
+    })
+}
+
+fn get_available_interfaces() -> Result<Vec<(String, std::net::IpAddr)>, ()> {
+    let network_interfaces = load_interfaces()?;
 
     log::info!("Available interfaces are:");
     for (index, (name, addr)) in network_interfaces.iter().enumerate() {
@@ -17,6 +119,7 @@ fn get_available_interfaces() -> Result<Vec<(String, std::net::IpAddr)>, ()> {
     Ok(network_interfaces)
 }
 
+#[allow(clippy::unused_unit)]
 fn get_user_input() -> Result<usize, ()> {
     log::info!("Please select interface Id: ");
     std::io::stdout().flush().map_err(|err| {
@@ -48,12 +151,33 @@ fn get_user_input() -> Result<usize, ()> {
     })
 }
 
-pub fn select_network() -> Result<std::net::IpAddr, ()> {
+/// Address of network interface with given name (IPv4 address is preferred)
+fn get_interface_address(name: &str) -> Result<ListenAddress, ()> {
+    let addresses: Vec<std::net::IpAddr> = load_interfaces()?
+        .into_iter()
+        .filter(|(x, _)| x == name)
+        .map(|(_, addr)| addr)
+        .collect();
+
+    match addresses
+        .iter()
+        .find(|x| x.is_ipv4())
+        .or_else(|| addresses.first())
+    {
+        Some(addr) => Ok(ListenAddress::on_interface(name, *addr)),
+        None => {
+            log::error!("Network interface \"{}\" does not exist", name);
+            Err(())
+        }
+    }
+}
+
+fn select_network_interactively() -> Result<ListenAddress, ()> {
     let interfaces = get_available_interfaces()?;
     let index = get_user_input()?;
 
     match interfaces.get(index) {
-        Some(x) => Ok(x.1),
+        Some((name, ip)) => Ok(ListenAddress::on_interface(name, *ip)),
         None => {
             log::error!("Used index is out of options");
             Err(())
@@ -61,19 +185,172 @@ pub fn select_network() -> Result<std::net::IpAddr, ()> {
     }
 }
 
+/// Get address of listening sockets - user is asked only if network was not required and
+/// standard input is terminal
+pub fn select_network(target: Option<BindTarget>) -> Result<ListenAddress, ()> {
+    match target {
+        Some(BindTarget::Address(addr)) => Ok(addr),
+        Some(BindTarget::Interface(name)) => get_interface_address(&name),
+        Some(BindTarget::All) => Ok(ListenAddress {
+            ip: std::net::IpAddr::V6(std::net::Ipv6Addr::UNSPECIFIED),
+            scope_id: 0,
+        }),
+        None => match std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+            true => select_network_interactively(),
+            false => {
+                log::error!(
+                    "Network was not specified and standard input is not terminal. Use --bind <ip>, --interface <name> or --bind-all"
+                );
+                Err(())
+            }
+        },
+    }
+}
+
+fn bind_socket(addr: std::net::SocketAddr) -> std::io::Result<tokio::net::TcpListener> {
+    let socket = socket2::Socket::new(
+        socket2::Domain::for_address(addr),
+        socket2::Type::STREAM,
+        Some(socket2::Protocol::TCP),
+    )?;
+
+    // Unspecified IPv6 address accepts IPv4 clients too
+    if addr.is_ipv6() && addr.ip().is_unspecified() {
+        socket.set_only_v6(false)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+
+    tokio::net::TcpListener::from_std(socket.into())
+}
+
+/// Bind listening socket - unspecified IPv6 address listens on both IPv6 and IPv4 (IPv4 only if
+/// IPv6 is not available)
+pub fn bind_listener(addr: std::net::SocketAddr) -> std::io::Result<tokio::net::TcpListener> {
+    match bind_socket(addr) {
+        Err(err)
+            if addr.ip() == std::net::IpAddr::V6(std::net::Ipv6Addr::UNSPECIFIED)
+                && err.kind() != std::io::ErrorKind::AddrInUse =>
+        {
+            log::warn!("Unable to listen on IPv6 ({}), only IPv4 is used", err);
+            bind_socket(std::net::SocketAddr::new(
+                std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+                addr.port(),
+            ))
+        }
+        x => x,
+    }
+}
+
+/// Addresses clients can connect to - all addresses of network interfaces for unspecified
+/// address
+fn connection_hints(
+    addr: std::net::SocketAddr,
+    interfaces: Vec<(String, std::net::IpAddr)>,
+) -> Vec<String> {
+    if !addr.ip().is_unspecified() {
+        return vec![addr.to_string()];
+    }
+
+    interfaces
+        .into_iter()
+        .filter(|(_, ip)| addr.is_ipv6() || ip.is_ipv4())
+        .map(|(name, ip)| match ip {
+            std::net::IpAddr::V4(ip) => format!("{}:{}", ip, addr.port()),
+            std::net::IpAddr::V6(ip) if is_link_local(&ip) => {
+                format!("[{}%{}]:{}", ip, name, addr.port())
+            }
+            std::net::IpAddr::V6(ip) => format!("[{}]:{}", ip, addr.port()),
+        })
+        .collect()
+}
+
+/// Address of client - IPv4 client of dual-stack socket gets back its IPv4 address
+pub fn client_address(addr: std::net::SocketAddr) -> std::net::SocketAddr {
+    std::net::SocketAddr::new(addr.ip().to_canonical(), addr.port())
+}
+
+/// Log addresses clients can connect to listener
+pub fn log_connection_hints(listener: &tokio::net::TcpListener) {
+    let Ok(addr) = listener.local_addr() else {
+        return;
+    };
+    let interfaces = match addr.ip().is_unspecified() {
+        true => load_interfaces().unwrap_or_default(),
+        false => Vec::new(),
+    };
+
+    for hint in connection_hints(addr, interfaces) {
+        log::info!("Clients can connect to: {}", hint);
+    }
+}
+
+/// Byte stream of client connection
+pub trait Stream: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send {}
+
+impl<T> Stream for T where T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send {}
+
+/// Accepted connection of client
+pub struct Connection {
+    stream: tokio::io::BufReader<Box<dyn Stream>>,
+    /// Name of account of logged in client
+    account: Option<String>,
+    /// Nickname chosen before joining (name of bot)
+    nickname: Option<String>,
+    /// Options of communication negotiated by client
+    options: handshake::Options,
+    /// First line of client read during negotiation of protocol - it is passed to game
+    pending_input: Option<String>,
+}
+
+impl Connection {
+    /// Connection of built-in bot playing on playboard with given edge under given name
+    pub fn bot(name: &str, size: usize) -> Connection {
+        let (client, server) = tokio::io::duplex(1024);
+        tokio::spawn(bot::play(client, size));
+
+        Connection {
+            stream: tokio::io::BufReader::new(Box::new(server)),
+            // Bot has no account, so its games are not rated
+            account: None,
+            nickname: Some(name.to_owned()),
+            // Bot reads structured messages
+            options: handshake::Options {
+                json: true,
+                ..Default::default()
+            },
+            pending_input: None,
+        }
+    }
+}
+
 pub struct Player {
-    id: super::super::PlayerId,
+    role: Role,
+    nickname: Option<String>,
+    /// Name of account of logged in client
+    account: Option<String>,
+    client_id: ClientId,
     tx_client: tokio::sync::mpsc::Sender<String>,
     last_msg_ends_with_new_line: bool,
+    /// Options of communication negotiated by client
+    options: handshake::Options,
 }
 
 async fn player_communication(
-    player_id: super::super::PlayerId,
-    mut stream: tokio::net::TcpStream,
-    tx_game: tokio::sync::mpsc::Sender<MsgFromPlayer<tokio::net::TcpStream, String>>,
+    client_id: ClientId,
+    stream: tokio::io::BufReader<Box<dyn Stream>>,
+    tx_game: tokio::sync::mpsc::Sender<MsgFromPlayer<Connection, String>>,
     mut rx_client: tokio::sync::mpsc::Receiver<String>,
+    pending_input: Option<String>,
 ) {
-    let (reader, mut writer) = stream.split();
+    let (reader, mut writer) = tokio::io::split(stream);
+
+    if let Some(line) = pending_input {
+        let line = line.strip_suffix('\n').unwrap_or(&line).to_owned();
+        let _ = tx_game.send(MsgFromPlayer::Msg(client_id, line)).await;
+    }
 
     let mut buff = tokio::io::BufReader::new(reader);
 
@@ -84,17 +361,26 @@ async fn player_communication(
             // Reading input from client
             msg_length = buff.read_line(&mut line) => {
                 // Connection closed
-                if msg_length.unwrap() == 0 {
-                    tx_game.send(MsgFromPlayer::Leave(player_id)).await;
+                if msg_length.unwrap_or(0) == 0 {
+                    let _ = tx_game.send(MsgFromPlayer::Leave(client_id)).await;
                     return ;
                 }
                 // Send message from client
-                tx_game.send(MsgFromPlayer::Msg(player_id, line[..line.len() - 1].to_owned())).await;
+                let _ = tx_game.send(MsgFromPlayer::Msg(client_id, line[..line.len() - 1].to_owned())).await;
                 line.clear();
             }
             // Sending message to client
             msg = rx_client.recv() => {
-                writer.write_all(msg.unwrap().as_bytes()).await;
+                match msg {
+                    Some(msg) => {
+                        let _ = writer.write_all(msg.as_bytes()).await;
+                    }
+                    // Player was dropped by game - close connection
+                    None => {
+                        let _ = writer.shutdown().await;
+                        return;
+                    }
+                }
             }
         }
     }
@@ -102,110 +388,651 @@ async fn player_communication(
 
 impl Player {
     pub fn new(
-        player_id: super::super::PlayerId,
-        stream: tokio::net::TcpStream,
-        tx_game: tokio::sync::mpsc::Sender<MsgFromPlayer<tokio::net::TcpStream, String>>,
+        client_id: ClientId,
+        role: Role,
+        connection: Connection,
+        tx_game: tokio::sync::mpsc::Sender<MsgFromPlayer<Connection, String>>,
     ) -> Player {
         let (tx_client, rx_client) = tokio::sync::mpsc::channel(5);
 
-        tokio::spawn(player_communication(player_id, stream, tx_game, rx_client));
+        tokio::spawn(player_communication(
+            client_id,
+            connection.stream,
+            tx_game,
+            rx_client,
+            connection.pending_input,
+        ));
 
         Player {
-            id: player_id,
+            role,
+            nickname: connection.nickname,
+            account: connection.account,
+            client_id,
             tx_client,
             last_msg_ends_with_new_line: true,
+            options: connection.options,
         }
     }
 }
 
-#[async_trait::async_trait]
-impl super::PlayerTrait for Player {
-    type FieldRepresentation = String;
+fn player_info_to_string(info: &super::PlayerInfo) -> String {
+    match info.rating {
+        Some(rating) => format!("{} [{}]", info.nickname, rating),
+        None => info.nickname.clone(),
+    }
+}
 
-    async fn send_msg_to_player(&mut self, msg: super::MsgToPlayer<Self::FieldRepresentation>) {
-        let mut text = match &msg {
-            super::MsgToPlayer::WelcomePlayer => "Welcome player\r\n".to_owned(),
-            super::MsgToPlayer::WaitingForOtherPlayer => {
-                "We are waiting for another player\r\n".to_owned()
-            }
-            super::MsgToPlayer::PlayersAreReady => "Both players are ready\r\n\r\n".to_owned(),
-            super::MsgToPlayer::OtherPlayerLeave => {
-                "Other player leave game. Congratulation you win!\r\n".to_owned()
-            }
-            super::MsgToPlayer::YourAreOnMove => "Now you are on move: ".to_owned(),
-            super::MsgToPlayer::OtherPlayerIsOnMove => "Now is other player on move\r\n".to_owned(),
-            super::MsgToPlayer::InvalidInput => {
-                "You pass invalid input. Please repeat your input: ".to_owned()
+fn result_reason_to_string(reason: &super::super::ResultReason) -> &'static str {
+    match reason {
+        super::super::ResultReason::LineCompleted => "line was completed",
+        super::super::ResultReason::BoardExhausted => "no line can be completed",
+        super::super::ResultReason::Resignation => "player resigned",
+        super::super::ResultReason::Agreement => "players agreed on draw",
+        super::super::ResultReason::Timeout => "player ran out of time",
+    }
+}
+
+fn duration_to_string(duration: std::time::Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Text of message for client of text protocol (without prefix with name of player)
+pub fn msg_to_text(
+    msg: &super::MsgToPlayer<super::super::converters::RenderedPlayboard>,
+    role: Role,
+    unicode: bool,
+    colors: bool,
+) -> String {
+    match msg {
+        super::MsgToPlayer::WelcomeLobby => {
+            "Welcome in lobby. Send \"create <size> [<win length>]\", \"list\", \"join <room id>\", \"play <size> [<rating>]\" or \"resume <session token>\"\r\n"
+                .to_owned()
+        }
+        super::MsgToPlayer::RoomCreated(room_id) => format!("Room {} was created\r\n", room_id),
+        super::MsgToPlayer::Rooms(rooms) => match rooms.is_empty() {
+            true => "No room is open\r\n".to_owned(),
+            false => rooms
+                .iter()
+                .map(|room| {
+                    format!(
+                        "Room {} - playboard {}x{}, win length {}, clients {}\r\n",
+                        room.id,
+                        room.options.size,
+                        room.options.size,
+                        room.options.win_length,
+                        room.clients
+                    )
+                })
+                .collect(),
+        },
+        super::MsgToPlayer::RoomNotFound => "Required room does not exist\r\n".to_owned(),
+        super::MsgToPlayer::SearchingOpponent(size) => format!(
+            "Searching opponent for playboard {}x{}. Send \"cancel\" to leave queue\r\n",
+            size, size
+        ),
+        super::MsgToPlayer::QueueLeft => "You are not searching opponent\r\n".to_owned(),
+        super::MsgToPlayer::OpponentFound(room_id) => {
+            format!("Opponent was found in room {}\r\n", room_id)
+        }
+        super::MsgToPlayer::WelcomeTournament => {
+            "Welcome in tournament. Games start with next round\r\n".to_owned()
+        }
+        super::MsgToPlayer::NotParticipant => {
+            "You are not registered participant of tournament. Goodbye!\r\n".to_owned()
+        }
+        super::MsgToPlayer::WaitingForNextRound => {
+            "Please wait for start of next round\r\n".to_owned()
+        }
+        super::MsgToPlayer::RoundStarted(round, rounds) => {
+            format!("Round {} of {} starts\r\n", round, rounds)
+        }
+        super::MsgToPlayer::Bye => {
+            "You have no opponent in this round and get point\r\n".to_owned()
+        }
+        super::MsgToPlayer::OpponentForfeited => {
+            "Your opponent forfeited game. Congratulation you win!\r\n".to_owned()
+        }
+        super::MsgToPlayer::Standings(standings) => standings
+            .iter()
+            .map(|standing| format!("{}\r\n", standing))
+            .collect(),
+        super::MsgToPlayer::WelcomePlayer => "Welcome player\r\n".to_owned(),
+        super::MsgToPlayer::WelcomeSpectator => {
+            "Welcome spectator. You can watch game of other players\r\n".to_owned()
+        }
+        super::MsgToPlayer::Spectators(count) => {
+            format!("Number of spectators watching game: {}\r\n", count)
+        }
+        super::MsgToPlayer::SpectatorsCannotPlay => {
+            "Spectators are not allowed to play\r\n".to_owned()
+        }
+        super::MsgToPlayer::ServerFull => {
+            "Server is full. Please try again later\r\n".to_owned()
+        }
+        super::MsgToPlayer::QueuedAsChallenger(position) => format!(
+            "All places are taken. You are {}. in queue for next game\r\n",
+            position
+        ),
+        super::MsgToPlayer::EnterNickname => {
+            "Please choose your nickname by sending \"name <nickname>\": ".to_owned()
+        }
+        super::MsgToPlayer::InvalidNickname(err) => match err {
+            super::super::nickname::NicknameError::InvalidLength => format!(
+                "Nickname has to have {} - {} characters. Please repeat your input: ",
+                super::super::nickname::MIN_LENGTH,
+                super::super::nickname::MAX_LENGTH
+            ),
+            super::super::nickname::NicknameError::InvalidCharacters => {
+                "Nickname may contain only letters, digits, \"-\" and \"_\". Please repeat your input: "
+                    .to_owned()
             }
-            super::MsgToPlayer::AlreadyTaken => {
-                "Required field is already taken. Please repeat your input: ".to_owned()
+            super::super::nickname::NicknameError::AlreadyUsed => {
+                "Nickname is already used. Please repeat your input: ".to_owned()
             }
-            super::MsgToPlayer::YouAreNotOnMove => {
-                "You are not on move. Please wait till other player move\r\n".to_owned()
+        },
+        super::MsgToPlayer::SessionToken(token) => format!(
+            "Your session token is {}. Send \"resume {}\" after reconnection to continue interrupted game\r\n",
+            token, token
+        ),
+        super::MsgToPlayer::EnterSessionToken => {
+            "Game is interrupted. Send \"resume <token>\" to continue it: ".to_owned()
+        }
+        super::MsgToPlayer::InvalidSessionToken => {
+            "Session token is not valid. Please repeat your input: ".to_owned()
+        }
+        super::MsgToPlayer::GameResumed => "Game continues\r\n".to_owned(),
+        super::MsgToPlayer::WaitingForOtherPlayer => {
+            "We are waiting for another player\r\n".to_owned()
+        }
+        super::MsgToPlayer::PlayersAreReady { circle, cross } => format!(
+            "Both players are ready - {} (o) vs. {} (x)\r\n\r\n",
+            player_info_to_string(circle),
+            player_info_to_string(cross)
+        ),
+        super::MsgToPlayer::OtherPlayerLeave => {
+            "Other player leave game. Congratulation you win!\r\n".to_owned()
+        }
+        super::MsgToPlayer::OtherPlayerDisconnected(grace) => format!(
+            "Other player disconnected. Waiting {} for reconnection\r\n",
+            duration_to_string(*grace)
+        ),
+        super::MsgToPlayer::OtherPlayerReconnected => {
+            "Other player reconnected\r\n".to_owned()
+        }
+        super::MsgToPlayer::YourAreOnMove => "Now you are on move: ".to_owned(),
+        super::MsgToPlayer::OtherPlayerIsOnMove(nickname) => {
+            format!("Now is {} on move\r\n", nickname)
+        }
+        super::MsgToPlayer::InvalidInput => {
+            "You pass invalid input. Please repeat your input: ".to_owned()
+        }
+        super::MsgToPlayer::AlreadyTaken => {
+            "Required field is already taken. Please repeat your input: ".to_owned()
+        }
+        super::MsgToPlayer::YouAreNotOnMove => {
+            "You are not on move. Please wait till other player move\r\n".to_owned()
+        }
+        super::MsgToPlayer::SwapAvailable => {
+            "You can take over first move by sending \"swap\" instead of your move\r\n"
+                .to_owned()
+        }
+        super::MsgToPlayer::SwapNotAvailable => {
+            "Swap of sides is not possible now. Please repeat your input: ".to_owned()
+        }
+        super::MsgToPlayer::SidesSwapped => match role {
+            Role::Player(player_id) => format!(
+                "Players swapped sides, now you play for \"{}\"\r\n",
+                player_id
+            ),
+            Role::Spectator | Role::Challenger | Role::Guest => {
+                "Players swapped sides\r\n".to_owned()
             }
+        },
+
+        super::MsgToPlayer::Clock(state) => match role {
+            Role::Player(player_id) => format!(
+                "Remaining time - you: {}, other player: {}\r\n",
+                duration_to_string(state.get(player_id)),
+                duration_to_string(state.get(!player_id))
+            ),
+            Role::Spectator | Role::Challenger | Role::Guest => format!(
+                "Remaining time - o: {}, x: {}\r\n",
+                duration_to_string(state.circle),
+                duration_to_string(state.cross)
+            ),
+        },
+        super::MsgToPlayer::DrawOfferSent => "Draw was offered to other player\r\n".to_owned(),
+        super::MsgToPlayer::DrawOffered => {
+            "Other player offers draw. Send \"draw\" to accept or \"no\" to decline\r\n"
+                .to_owned()
+        }
+        super::MsgToPlayer::DrawOfferDeclined => {
+            "Other player declined your draw offer\r\n".to_owned()
+        }
+        super::MsgToPlayer::YouWon(reason) => format!(
+            "Congratulation, you win ({}).\r\n",
+            result_reason_to_string(reason)
+        ),
+        super::MsgToPlayer::YouLose(reason) => format!(
+            "Unfortunately you lose ({}).\r\n",
+            result_reason_to_string(reason)
+        ),
+        super::MsgToPlayer::PlayerWon(player_id, nickname, reason) => format!(
+            "Player {} (\"{}\") won ({}).\r\n",
+            nickname,
+            player_id,
+            result_reason_to_string(reason)
+        ),
+        super::MsgToPlayer::Draw(reason) => {
+            format!("Nobody win ({})\r\n", result_reason_to_string(reason))
+        }
+        super::MsgToPlayer::RatingChanged(change) => format!(
+            "Your rating is {} ({:+})\r\n",
+            change.rating, change.change
+        ),
+        super::MsgToPlayer::Leaderboard(entries) => match entries.is_empty() {
+            true => "No rated game was played yet\r\n".to_owned(),
+            false => entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| {
+                    format!(
+                        "{}. {} - rating {}, games {}\r\n",
+                        index + 1,
+                        entry.account,
+                        entry.rating,
+                        entry.games
+                    )
+                })
+                .collect(),
+        },
+        super::MsgToPlayer::Score(score) => format!(
+            "Score of match - wins: {}, losses: {}, draws: {}\r\n",
+            score.wins, score.losses, score.draws
+        ),
+        super::MsgToPlayer::YouWonMatch => "Congratulation, you win the match.\r\n".to_owned(),
+        super::MsgToPlayer::YouLostMatch => "Unfortunately you lose the match.\r\n".to_owned(),
+        super::MsgToPlayer::MatchDrawn => "Nobody win the match\r\n".to_owned(),
+        super::MsgToPlayer::RematchQuestion => {
+            "Do you want to play again? [yes/no]: ".to_owned()
+        }
+        super::MsgToPlayer::WaitingForRematchAnswer => {
+            "We are waiting for answer of other player\r\n".to_owned()
+        }
+        super::MsgToPlayer::OtherPlayerWantsRematch => {
+            "Other player wants to play again\r\n".to_owned()
+        }
+        super::MsgToPlayer::OtherPlayerDeclinedRematch => {
+            "Other player does not want to play again\r\n".to_owned()
+        }
+        super::MsgToPlayer::Goodbye => "Thank you for playing. Goodbye!\r\n".to_owned(),
+        super::MsgToPlayer::Playboard(playboard) => {
+            let text = match unicode || colors {
+                true => super::super::converters::board_to_string(&playboard.board, unicode, colors),
+                false => playboard.text.clone(),
+            };
+            format!(
+                "------------------\r\nCurrent game field\r\n\r\n{}\r\n",
+                text
+            )
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl super::PlayerTrait for Player {
+    type FieldRepresentation = super::super::converters::RenderedPlayboard;
 
-            super::MsgToPlayer::YouWon => "Congratulation, you win.\r\n".to_owned(),
-            super::MsgToPlayer::YouLose => "Unfortunately you lose.\r\n".to_owned(),
-            super::MsgToPlayer::Draw => "Nobody win\r\n".to_owned(),
-            super::MsgToPlayer::Playboard(T) => {
-                format!("------------------\r\nCurrent game field\r\n\r\n{}\r\n", T)
+    async fn send_msg_to_player(&mut self, msg: super::MsgToPlayer<Self::FieldRepresentation>) {
+        let options = self.options;
+
+        if options.json {
+            match serde_json::to_string(&msg) {
+                Ok(text) => {
+                    let _ = self.tx_client.send(text + "\n").await;
+                }
+                Err(err) => log::error!("Unable to encode message for {}: {}", self.client_id, err),
             }
-        };
+            return;
+        }
+
+        let mut text = msg_to_text(&msg, self.role, options.unicode, options.colors);
 
         // Add player name prefix
         match msg {
             super::MsgToPlayer::Playboard(_) => (),
             _ => {
-                text = format!(
-                    "[{}] {}",
-                    <Player as super::PlayerTrait>::get_player_id(self),
-                    text
-                )
+                text = match &self.nickname {
+                    Some(nickname) => format!("[{} ({})] {}", nickname, self.role, text),
+                    None => format!("[{}] {}", self.role, text),
+                }
             }
         }
         match msg {
-            super::MsgToPlayer::YourAreOnMove => {
+            super::MsgToPlayer::YourAreOnMove
+            | super::MsgToPlayer::RematchQuestion
+            | super::MsgToPlayer::EnterNickname
+            | super::MsgToPlayer::InvalidNickname(_)
+            | super::MsgToPlayer::EnterSessionToken => {
                 self.last_msg_ends_with_new_line = false;
             }
-            super::MsgToPlayer::OtherPlayerLeave => {
+            super::MsgToPlayer::OtherPlayerLeave
+            | super::MsgToPlayer::RoundStarted(_, _)
+            | super::MsgToPlayer::OpponentForfeited
+            | super::MsgToPlayer::Standings(_)
+            | super::MsgToPlayer::Spectators(_)
+            | super::MsgToPlayer::QueuedAsChallenger(_)
+            | super::MsgToPlayer::Leaderboard(_)
+            | super::MsgToPlayer::SessionToken(_)
+            | super::MsgToPlayer::OtherPlayerDisconnected(_)
+            | super::MsgToPlayer::OtherPlayerReconnected
+            | super::MsgToPlayer::Clock(_)
+            | super::MsgToPlayer::DrawOffered
+            | super::MsgToPlayer::DrawOfferDeclined
+            | super::MsgToPlayer::YouWon(_)
+            | super::MsgToPlayer::YouLose(_)
+            | super::MsgToPlayer::PlayerWon(_, _, _)
+            | super::MsgToPlayer::Draw(_)
+            | super::MsgToPlayer::OtherPlayerWantsRematch
+            | super::MsgToPlayer::OtherPlayerDeclinedRematch => {
                 if !self.last_msg_ends_with_new_line {
                     text = format!("\r\n{}", text);
                 }
+                self.last_msg_ends_with_new_line = true;
             }
             _ => self.last_msg_ends_with_new_line = true,
         }
 
-        self.tx_client.send(text).await;
+        let _ = self.tx_client.send(text).await;
+    }
+
+    fn get_role(&self) -> Role {
+        self.role
+    }
+
+    fn set_role(&mut self, role: Role) {
+        self.role = role;
+    }
+
+    fn get_nickname(&self) -> Option<&str> {
+        self.nickname.as_deref()
     }
 
-    fn get_player_id(&self) -> super::super::PlayerId {
-        self.id
+    fn get_account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    fn set_nickname(&mut self, nickname: String) {
+        self.nickname = Some(nickname);
+    }
+
+    fn get_client_id(&self) -> ClientId {
+        self.client_id
     }
 }
 
 pub struct PlayerManager {
-    tx: tokio::sync::mpsc::Sender<super::MsgFromPlayer<tokio::net::TcpStream, String>>,
-    rx: tokio::sync::mpsc::Receiver<super::MsgFromPlayer<tokio::net::TcpStream, String>>,
+    tx: tokio::sync::mpsc::Sender<super::MsgFromPlayer<Connection, String>>,
+    rx: tokio::sync::mpsc::Receiver<super::MsgFromPlayer<Connection, String>>,
+}
+
+/// Send text (or JSON when client switched protocol) to client during authentication
+async fn write_auth_msg(
+    stream: &mut tokio::io::BufReader<Box<dyn Stream>>,
+    json: bool,
+    text: &str,
+    value: serde_json::Value,
+) {
+    let _ = match json {
+        true => {
+            stream
+                .get_mut()
+                .write_all(format!("{}\n", value).as_bytes())
+                .await
+        }
+        false => stream.get_mut().write_all(text.as_bytes()).await,
+    };
+}
+
+/// Let client log in, register new account or continue as guest
+async fn authenticate(
+    client_id: ClientId,
+    mut stream: tokio::io::BufReader<Box<dyn Stream>>,
+    options: handshake::Options,
+    mut pending_input: Option<String>,
+    accounts: std::sync::Arc<super::super::accounts::AccountStore>,
+    tx: tokio::sync::mpsc::Sender<super::MsgFromPlayer<Connection, String>>,
+) {
+    let mut line = String::new();
+
+    write_auth_msg(
+        &mut stream,
+        options.json,
+        "Welcome. Send \"login <name> <password>\", \"register <name> <password>\" or \"guest\": ",
+        serde_json::json!({ "type": "authenticate" }),
+    )
+    .await;
+
+    loop {
+        line.clear();
+
+        // Line typed before prompt is first input
+        match pending_input.take() {
+            Some(input) => line = input,
+            // Connection closed
+            None if stream.read_line(&mut line).await.unwrap_or(0) == 0 => return,
+            None => (),
+        }
+
+        let command = match options.json {
+            true => serde_json::from_str::<super::AuthCommand>(&line).ok(),
+            false => {
+                let words: Vec<&str> = line.split_whitespace().collect();
+                match words.as_slice() {
+                    ["guest"] => Some(super::AuthCommand::Guest),
+                    ["login", name, password] => Some(super::AuthCommand::Login {
+                        name: name.to_string(),
+                        password: password.to_string(),
+                    }),
+                    ["register", name, password] => Some(super::AuthCommand::Register {
+                        name: name.to_string(),
+                        password: password.to_string(),
+                    }),
+                    _ => None,
+                }
+            }
+        };
+
+        let result = match command {
+            Some(command) => command.authenticate(&accounts).await,
+            None => {
+                write_auth_msg(
+                    &mut stream,
+                    options.json,
+                    "You pass invalid input. Please repeat your input: ",
+                    serde_json::json!({ "type": "invalid_input" }),
+                )
+                .await;
+                continue;
+            }
+        };
+
+        match result {
+            Ok(account) => {
+                if let Some(name) = &account {
+                    log::info!("Client {} logged in as {}", client_id, name);
+                }
+
+                let connection = Connection {
+                    stream,
+                    account,
+                    nickname: None,
+                    options,
+                    pending_input: None,
+                };
+                let _ = tx
+                    .send(super::MsgFromPlayer::Join(client_id, connection))
+                    .await;
+                return;
+            }
+            Err(err) => {
+                log::info!("Authentication of client {} failed: {}", client_id, err);
+
+                write_auth_msg(
+                    &mut stream,
+                    options.json,
+                    &format!("Unable to log in - {}. Please repeat your input: ", err),
+                    serde_json::json!({
+                        "type": "authentication_failed",
+                        "data": err.to_string(),
+                    }),
+                )
+                .await;
+            }
+        }
+    }
+}
+
+/// Send greeting and negotiate protocol by first line of client - other first line is returned
+/// as input of client
+///
+/// Client which sends nothing in time uses default options. None is returned if connection is
+/// closed or client speaks incompatible protocol.
+async fn negotiate_protocol(
+    client_id: ClientId,
+    stream: &mut tokio::io::BufReader<Box<dyn Stream>>,
+) -> Option<(handshake::Options, Option<String>)> {
+    let mut options = handshake::Options::default();
+
+    // Server announces its protocol first, so client knows what it can choose
+    let _ = stream
+        .get_mut()
+        .write_all(handshake::greeting().as_bytes())
+        .await;
+
+    // Waiting for data does not consume it, so nothing is lost by timeout
+    match tokio::time::timeout(handshake::TIMEOUT, stream.fill_buf()).await {
+        Ok(Ok([])) | Ok(Err(_)) => return None,
+        Ok(Ok(_)) => (),
+        Err(_) => return Some((options, None)),
+    }
+
+    let mut line = String::new();
+    if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+        return None;
+    }
+
+    match handshake::handle_line(&line, &mut options) {
+        Some(handshake::Reply::Accepted(reply)) => {
+            let _ = stream.get_mut().write_all(reply.as_bytes()).await;
+            Some((options, None))
+        }
+        Some(handshake::Reply::Rejected(reply)) => {
+            log::info!("Client {} speaks incompatible protocol", client_id);
+            let _ = stream.get_mut().write_all(reply.as_bytes()).await;
+            let _ = stream.get_mut().shutdown().await;
+            None
+        }
+        None => Some((options, Some(line))),
+    }
+}
+
+/// Negotiate protocol, authenticate (if accounts are used) and pass connection of new client
+/// to game
+async fn handle_new_connection(
+    client_id: ClientId,
+    stream: Box<dyn Stream>,
+    accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    tx: tokio::sync::mpsc::Sender<super::MsgFromPlayer<Connection, String>>,
+) {
+    let mut stream = tokio::io::BufReader::new(stream);
+    let (options, pending_input) = match negotiate_protocol(client_id, &mut stream).await {
+        Some(x) => x,
+        None => return,
+    };
+
+    match accounts {
+        Some(accounts) => {
+            authenticate(client_id, stream, options, pending_input, accounts, tx).await;
+        }
+        None => {
+            let connection = Connection {
+                stream,
+                account: None,
+                nickname: None,
+                options,
+                pending_input,
+            };
+            let _ = tx
+                .send(super::MsgFromPlayer::Join(client_id, connection))
+                .await;
+        }
+    }
+}
+
+/// Accept connection of new client - client joins game after negotiation of protocol
+pub async fn accept_connection(
+    client_id: ClientId,
+    stream: Box<dyn Stream>,
+    accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    tx: tokio::sync::mpsc::Sender<super::MsgFromPlayer<Connection, String>>,
+) {
+    tokio::spawn(handle_new_connection(client_id, stream, accounts, tx));
+}
+
+/// Finish TLS handshake of new client before accepting it
+async fn accept_tls_connection(
+    client_id: ClientId,
+    stream: tokio::net::TcpStream,
+    tls: tokio_rustls::TlsAcceptor,
+    accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    tx: tokio::sync::mpsc::Sender<super::MsgFromPlayer<Connection, String>>,
+) {
+    match tls.accept(stream).await {
+        Ok(stream) => accept_connection(client_id, Box::new(stream), accounts, tx).await,
+        Err(err) => log::info!("TLS handshake of client {} failed: {}", client_id, err),
+    }
 }
 
 async fn connection_listener(
     listener: tokio::net::TcpListener,
-    tx: tokio::sync::mpsc::Sender<super::MsgFromPlayer<tokio::net::TcpStream, String>>,
+    tls: Option<tokio_rustls::TlsAcceptor>,
+    accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    tx: tokio::sync::mpsc::Sender<super::MsgFromPlayer<Connection, String>>,
 ) {
+    let mut next_client_id = 0;
+
     loop {
         let (stream, address) = listener.accept().await.unwrap();
+        let address = client_address(address);
+        let client_id = ClientId(next_client_id);
+        next_client_id += 1;
+
+        log::info!("New connection {} from {}", client_id, address);
 
-        tx.send(super::MsgFromPlayer::Join(stream)).await;
+        match &tls {
+            Some(tls) => {
+                tokio::spawn(accept_tls_connection(
+                    client_id,
+                    stream,
+                    tls.clone(),
+                    accounts.clone(),
+                    tx.clone(),
+                ));
+            }
+            None => {
+                accept_connection(client_id, Box::new(stream), accounts.clone(), tx.clone()).await
+            }
+        }
     }
 }
 
 async fn get_available_listener(
-    ip: std::net::IpAddr,
+    address: ListenAddress,
 ) -> tokio::io::Result<tokio::net::TcpListener> {
     for port in 49152..65535 {
-        let socket_addr = std::net::SocketAddr::new(ip, port);
-
-        match tokio::net::TcpListener::bind(socket_addr).await {
+        match bind_listener(address.with_port(port)) {
             Ok(x) => return Ok(x),
             Err(err) => match err.kind() {
                 std::io::ErrorKind::AddrInUse => continue,
@@ -218,48 +1045,227 @@ async fn get_available_listener(
 }
 
 impl PlayerManager {
-    pub fn new(listener: tokio::net::TcpListener) -> Self {
+    pub fn new(
+        listener: tokio::net::TcpListener,
+        tls: Option<tokio_rustls::TlsAcceptor>,
+        accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    ) -> Self {
         log::info!(
-            "TCP player manager running on address: {}",
-            listener.local_addr().unwrap()
+            "TCP player manager running on address: {}{}",
+            listener.local_addr().unwrap(),
+            match tls.is_some() {
+                true => " (TLS)",
+                false => "",
+            }
         );
+        log_connection_hints(&listener);
 
         let (tx, rx) = tokio::sync::mpsc::channel(10);
 
-        tokio::spawn(connection_listener(listener, tx.clone()));
+        tokio::spawn(connection_listener(listener, tls, accounts, tx.clone()));
 
         PlayerManager { tx, rx }
     }
 
-    pub async fn from_ip(ip: std::net::IpAddr) -> Result<PlayerManager, std::io::Error> {
-        let listener = get_available_listener(ip).await?;
-        Ok(Self::new(listener))
+    pub async fn from_ip(
+        address: ListenAddress,
+        tls: Option<tokio_rustls::TlsAcceptor>,
+        accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    ) -> Result<PlayerManager, std::io::Error> {
+        let listener = get_available_listener(address).await?;
+        Ok(Self::new(listener, tls, accounts))
     }
 
-    pub async fn from_socket_address<T>(addr: T) -> Result<PlayerManager, std::io::Error>
+    /// Player manager of clients passed to accept_connection by other listener (e.g. Unix
+    /// socket listener)
+    pub fn with_listener<F, L>(listener: F) -> Self
     where
-        T: tokio::net::ToSocketAddrs,
+        F: FnOnce(tokio::sync::mpsc::Sender<super::MsgFromPlayer<Connection, String>>) -> L,
+        L: std::future::Future<Output = ()> + Send + 'static,
     {
-        let listener = tokio::net::TcpListener::bind(addr).await?;
-        Ok(Self::new(listener))
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
+
+        tokio::spawn(listener(tx.clone()));
+
+        PlayerManager { tx, rx }
+    }
+
+    /// Player manager with single built-in bot playing on playboard with given edge
+    pub fn from_bot(name: &str, size: usize) -> Self {
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
+
+        let _ = tx.try_send(super::MsgFromPlayer::Join(
+            ClientId(0),
+            Connection::bot(name, size),
+        ));
+
+        PlayerManager { tx, rx }
+    }
+
+    pub async fn from_socket_address(
+        addr: std::net::SocketAddr,
+        tls: Option<tokio_rustls::TlsAcceptor>,
+        accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    ) -> Result<PlayerManager, std::io::Error> {
+        let listener = bind_listener(addr)?;
+        Ok(Self::new(listener, tls, accounts))
     }
 }
 
 #[async_trait::async_trait]
 impl super::PlayerManagerTrait for PlayerManager {
-    type NewPlayerData = tokio::net::TcpStream;
+    type NewPlayerData = Connection;
     type NewPlayer = Player;
     type PlayerMsg = String;
 
-    fn create_new_player<'a>(
+    fn create_new_player(
         &self,
-        player_id: super::super::PlayerId,
+        client_id: ClientId,
+        role: Role,
         player_data: Self::NewPlayerData,
     ) -> Self::NewPlayer {
-        Player::new(player_id, player_data, self.tx.clone())
+        Player::new(client_id, role, player_data, self.tx.clone())
     }
 
     async fn receive_new_message(&mut self) -> MsgFromPlayer<Self::NewPlayerData, Self::PlayerMsg> {
         return self.rx.recv().await.unwrap();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn index_of_interface() {
+        assert!(interface_index("lo").is_some());
+        assert_eq!(interface_index("no-such-interface"), None);
+    }
+
+    #[test]
+    fn parse_listen_address() {
+        assert_eq!(
+            "127.0.0.1".parse(),
+            Ok(ListenAddress {
+                ip: std::net::IpAddr::from([127, 0, 0, 1]),
+                scope_id: 0
+            })
+        );
+        assert_eq!(
+            "fe80::1%3"
+                .parse::<ListenAddress>()
+                .map(|x| x.with_port(5000)),
+            Ok("[fe80::1%3]:5000".parse().unwrap())
+        );
+        assert!("fe80::1".parse::<ListenAddress>().is_err());
+        assert!("127.0.0.1%3".parse::<ListenAddress>().is_err());
+    }
+
+    #[test]
+    fn hints_of_unspecified_address() {
+        let interfaces = vec![
+            ("lo".to_owned(), std::net::IpAddr::from([127, 0, 0, 1])),
+            ("eth0".to_owned(), "fe80::1".parse().unwrap()),
+            ("eth0".to_owned(), "2001:db8::1".parse().unwrap()),
+        ];
+
+        assert_eq!(
+            connection_hints("[::]:5000".parse().unwrap(), interfaces.clone()),
+            vec![
+                "127.0.0.1:5000",
+                "[fe80::1%eth0]:5000",
+                "[2001:db8::1]:5000"
+            ]
+        );
+        assert_eq!(
+            connection_hints("0.0.0.0:5000".parse().unwrap(), interfaces.clone()),
+            vec!["127.0.0.1:5000"]
+        );
+        assert_eq!(
+            connection_hints("[::1]:5000".parse().unwrap(), interfaces),
+            vec!["[::1]:5000"]
+        );
+    }
+
+    /// Connect client by in-memory stream and wait for its connection passed to game
+    async fn join(
+        first_line: &[u8],
+    ) -> (
+        tokio::io::BufReader<tokio::io::DuplexStream>,
+        Connection,
+        tokio::sync::mpsc::Sender<MsgFromPlayer<Connection, String>>,
+        tokio::sync::mpsc::Receiver<MsgFromPlayer<Connection, String>>,
+    ) {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+
+        client.write_all(first_line).await.unwrap();
+        accept_connection(ClientId(0), Box::new(server), None, tx.clone()).await;
+
+        let connection = match rx.recv().await {
+            Some(MsgFromPlayer::Join(ClientId(0), x)) => x,
+            _ => panic!("Client did not join"),
+        };
+
+        // Greeting of server is sent before anything else
+        let mut client = tokio::io::BufReader::new(client);
+        let mut line = String::new();
+        client.read_line(&mut line).await.unwrap();
+        assert_eq!(line, handshake::greeting());
+
+        (client, connection, tx, rx)
+    }
+
+    #[tokio::test]
+    async fn protocol_is_negotiated_before_join() {
+        let (mut client, connection, tx, _rx) = join(b"json\n").await;
+        assert!(connection.options.json);
+
+        let mut line = String::new();
+        client.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "{\"type\":\"protocol\",\"data\":\"json\"}\n");
+
+        // First message of game is already encoded by negotiated protocol
+        let mut player = Player::new(ClientId(0), Role::Guest, connection, tx);
+        super::super::PlayerTrait::send_msg_to_player(
+            &mut player,
+            super::super::MsgToPlayer::WelcomePlayer,
+        )
+        .await;
+
+        line.clear();
+        client.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "{\"type\":\"welcome_player\"}\n");
+    }
+
+    #[tokio::test]
+    async fn first_line_of_text_client_is_input() {
+        let (mut client, connection, tx, mut rx) = join(b"name alice\n").await;
+        assert_eq!(connection.options, handshake::Options::default());
+
+        let _player = Player::new(ClientId(0), Role::Guest, connection, tx);
+        assert!(matches!(
+            rx.recv().await,
+            Some(MsgFromPlayer::Msg(ClientId(0), line)) if line == "name alice"
+        ));
+
+        // Protocol can not be switched later
+        client.get_mut().write_all(b"json\n").await.unwrap();
+        assert!(matches!(
+            rx.recv().await,
+            Some(MsgFromPlayer::Msg(ClientId(0), line)) if line == "json"
+        ));
+    }
+
+    #[tokio::test]
+    async fn bot_has_nickname_without_account() {
+        let (tx, _rx) = tokio::sync::mpsc::channel(10);
+        let player = Player::new(ClientId(0), Role::Guest, Connection::bot("alice", 3), tx);
+
+        assert_eq!(
+            super::super::PlayerTrait::get_nickname(&player),
+            Some("alice")
+        );
+        assert_eq!(super::super::PlayerTrait::get_account(&player), None);
+    }
+}
@@ -0,0 +1,107 @@
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+/// Free fields of playboard as positions indexed from 1
+fn free_fields(board: &[Vec<Option<super::super::super::PlayerId>>]) -> Vec<(usize, usize)> {
+    board
+        .iter()
+        .enumerate()
+        .flat_map(|(row, fields)| {
+            fields
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| field.is_none())
+                .map(move |(column, _)| (row + 1, column + 1))
+        })
+        .collect()
+}
+
+/// Answer of bot to message of server - None if message does not require answer
+fn answer<R>(
+    msg: &serde_json::Value,
+    board: &mut super::super::super::converters::Board,
+    size: usize,
+    rng: &mut R,
+) -> Option<String>
+where
+    R: rand::Rng,
+{
+    match msg["type"].as_str()? {
+        "players_are_ready" => {
+            *board = vec![vec![None; size]; size];
+            None
+        }
+        "playboard" => {
+            if let Ok(x) = serde_json::from_value(msg["data"].clone()) {
+                *board = x;
+            }
+            None
+        }
+        "your_are_on_move" | "already_taken" | "invalid_input" => {
+            let answer = match free_fields(board).choose(rng) {
+                Some((row, column)) => {
+                    serde_json::json!({ "type": "move", "row": row, "column": column })
+                }
+                None => serde_json::json!({ "type": "resign" }),
+            };
+            Some(answer.to_string())
+        }
+        // Bot always agrees with next game
+        "rematch_question" => Some(serde_json::json!({ "type": "yes" }).to_string()),
+        _ => None,
+    }
+}
+
+/// Play random moves on playboard with given edge whenever server asks for move
+///
+/// Bot speaks JSON-lines protocol and chooses its moves from free fields of last playboard.
+pub async fn play<S>(stream: S, size: usize)
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let mut rng = rand::rngs::StdRng::from_entropy();
+    let mut board = vec![vec![None; size]; size];
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = tokio::io::BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let msg: serde_json::Value = match serde_json::from_str(&line) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+
+        if let Some(answer) = answer(&msg, &mut board, size, &mut rng) {
+            if writer
+                .write_all(format!("{}\n", answer).as_bytes())
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn move_to_free_field() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let mut board = vec![vec![None; 2]; 2];
+
+        let playboard = serde_json::json!({
+            "type": "playboard",
+            "data": [["o", "x"], [null, "o"]],
+        });
+        assert_eq!(answer(&playboard, &mut board, 2, &mut rng), None);
+
+        let on_move = serde_json::json!({ "type": "your_are_on_move" });
+        assert_eq!(
+            answer(&on_move, &mut board, 2, &mut rng),
+            Some(r#"{"column":1,"row":2,"type":"move"}"#.to_owned())
+        );
+    }
+}
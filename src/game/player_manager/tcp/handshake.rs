@@ -0,0 +1,306 @@
+/// Name of protocol spoken by server
+pub const PROTOCOL_NAME: &str = "noughts-and-crosses";
+
+/// Version of protocol spoken by server
+pub const PROTOCOL_VERSION: Version = Version { major: 1, minor: 1 };
+
+/// Line sent by client to switch to JSON-lines protocol without handshake
+const JSON_COMMAND: &str = "json";
+
+/// Time of waiting for first line of client before default options are used
+pub const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Version of protocol - clients with same major version are compatible
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl std::str::FromStr for Version {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (major, minor) = s.split_once('.').unwrap_or((s, "0"));
+
+        Ok(Version {
+            major: major.parse().map_err(|_| ())?,
+            minor: minor.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Options of communication chosen by server
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Options {
+    /// Messages are sent as JSON objects (one per line) instead of text
+    pub json: bool,
+    /// Symbols of players are highlighted by ANSI colors
+    pub colors: bool,
+    /// Playboard is drawn by unicode characters
+    pub unicode: bool,
+}
+
+impl Options {
+    fn to_names(self) -> Vec<&'static str> {
+        let mut names = vec![match self.json {
+            true => "json",
+            false => "text",
+        }];
+        if self.colors {
+            names.push("colors");
+        }
+        if self.unicode {
+            names.push("unicode");
+        }
+        names
+    }
+}
+
+/// Handshake announced by client
+#[derive(Debug, PartialEq)]
+struct Hello {
+    protocol: String,
+    version: String,
+    capabilities: Vec<String>,
+}
+
+/// Handshake announced by client as JSON
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonHello {
+    Hello {
+        protocol: String,
+        version: String,
+        #[serde(default)]
+        capabilities: Vec<String>,
+    },
+}
+
+/// Parse "hello <protocol> <version> [<capability> ...]" or its JSON form
+fn parse_hello(line: &str) -> Option<Hello> {
+    let line = line.trim();
+
+    if line.starts_with('{') {
+        return match serde_json::from_str::<JsonHello>(line).ok()? {
+            JsonHello::Hello {
+                protocol,
+                version,
+                capabilities,
+            } => Some(Hello {
+                protocol,
+                version,
+                capabilities,
+            }),
+        };
+    }
+
+    let mut words = line.split_whitespace();
+    if !words.next()?.eq_ignore_ascii_case("hello") {
+        return None;
+    }
+
+    Some(Hello {
+        protocol: words.next()?.to_owned(),
+        version: words.next()?.to_owned(),
+        capabilities: words.map(|x| x.to_lowercase()).collect(),
+    })
+}
+
+/// Choose options supported by both sides - unknown capabilities are ignored
+fn negotiate(hello: &Hello) -> Result<Options, String> {
+    if hello.protocol != PROTOCOL_NAME {
+        return Err(format!(
+            "unknown protocol \"{}\", server speaks {}",
+            hello.protocol, PROTOCOL_NAME
+        ));
+    }
+
+    let version: Version = hello
+        .version
+        .parse()
+        .map_err(|_| format!("invalid version \"{}\"", hello.version))?;
+
+    if version.major != PROTOCOL_VERSION.major {
+        return Err(format!(
+            "incompatible version {}, server supports {}.x",
+            version, PROTOCOL_VERSION.major
+        ));
+    }
+
+    let supports = |name: &str| hello.capabilities.iter().any(|x| x == name);
+    let json = supports("json");
+
+    // Colors and unicode affect only text messages
+    Ok(Options {
+        json,
+        colors: !json && supports("colors"),
+        unicode: !json && supports("unicode"),
+    })
+}
+
+/// Capabilities supported by server
+const CAPABILITIES: [&str; 4] = ["text", "json", "colors", "unicode"];
+
+/// First line sent by server to new client - announces protocol, version and capabilities
+pub fn greeting() -> String {
+    format!(
+        "hello {} {} {}\r\n",
+        PROTOCOL_NAME,
+        PROTOCOL_VERSION,
+        CAPABILITIES.join(" ")
+    )
+}
+
+/// Reply of server to first line of client concerning protocol
+pub enum Reply {
+    /// Options were negotiated - reply is encoded by new options
+    Accepted(String),
+    /// Client speaks incompatible protocol - connection should be closed
+    Rejected(String),
+}
+
+/// Handle first line of client choosing protocol - None if line is not related to protocol
+pub fn handle_line(line: &str, options: &mut Options) -> Option<Reply> {
+    if line.trim() == JSON_COMMAND {
+        options.json = true;
+        options.colors = false;
+        options.unicode = false;
+        return Some(Reply::Accepted(
+            "{\"type\":\"protocol\",\"data\":\"json\"}\n".to_owned(),
+        ));
+    }
+
+    let hello = parse_hello(line)?;
+
+    match negotiate(&hello) {
+        Ok(chosen) => {
+            *options = chosen;
+            let reply = match chosen.json {
+                true => format!(
+                    "{}\n",
+                    serde_json::json!({
+                        "type": "hello",
+                        "data": {
+                            "protocol": PROTOCOL_NAME,
+                            "version": PROTOCOL_VERSION.to_string(),
+                            "options": chosen.to_names(),
+                        },
+                    })
+                ),
+                false => format!(
+                    "hello {} {} {}\r\n",
+                    PROTOCOL_NAME,
+                    PROTOCOL_VERSION,
+                    chosen.to_names().join(" ")
+                ),
+            };
+            Some(Reply::Accepted(reply))
+        }
+        Err(err) => {
+            // Error is encoded as JSON only if client asked for JSON
+            let json = hello.capabilities.iter().any(|x| x == "json") || options.json;
+            let reply = match json {
+                true => format!(
+                    "{}\n",
+                    serde_json::json!({ "type": "incompatible_protocol", "data": err })
+                ),
+                false => format!("error Incompatible protocol - {}\r\n", err),
+            };
+            Some(Reply::Rejected(reply))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn greeting_is_hello_of_server() {
+        assert_eq!(
+            greeting(),
+            "hello noughts-and-crosses 1.1 text json colors unicode\r\n"
+        );
+        assert_eq!(
+            parse_hello(&greeting()).map(|hello| hello.capabilities),
+            Some(vec![
+                "text".to_owned(),
+                "json".to_owned(),
+                "colors".to_owned(),
+                "unicode".to_owned()
+            ])
+        );
+    }
+
+    #[test]
+    fn text_handshake() {
+        let mut options = Options::default();
+
+        match handle_line(
+            "hello noughts-and-crosses 1.0 text colors unicode\r\n",
+            &mut options,
+        ) {
+            Some(Reply::Accepted(reply)) => {
+                assert_eq!(
+                    reply,
+                    "hello noughts-and-crosses 1.1 text colors unicode\r\n"
+                )
+            }
+            _ => panic!("handshake was not accepted"),
+        }
+        assert_eq!(
+            options,
+            Options {
+                json: false,
+                colors: true,
+                unicode: true
+            }
+        );
+
+        assert!(handle_line("name alice\r\n", &mut options).is_none());
+    }
+
+    #[test]
+    fn json_handshake() {
+        let mut options = Options::default();
+
+        let line = r#"{"type":"hello","protocol":"noughts-and-crosses","version":"1","capabilities":["json","colors","future"]}"#;
+        assert!(matches!(
+            handle_line(line, &mut options),
+            Some(Reply::Accepted(_))
+        ));
+        assert_eq!(
+            options,
+            Options {
+                json: true,
+                colors: false,
+                unicode: false
+            }
+        );
+    }
+
+    #[test]
+    fn incompatible_handshake() {
+        let mut options = Options::default();
+
+        match handle_line("hello noughts-and-crosses 2.0", &mut options) {
+            Some(Reply::Rejected(reply)) => assert_eq!(
+                reply,
+                "error Incompatible protocol - incompatible version 2.0, server supports 1.x\r\n"
+            ),
+            _ => panic!("handshake was not rejected"),
+        }
+        assert!(matches!(
+            handle_line("hello chess 1.0", &mut options),
+            Some(Reply::Rejected(_))
+        ));
+        assert_eq!(options, Options::default());
+    }
+}
@@ -0,0 +1,138 @@
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+
+/// Validity of generated self-signed certificate
+const CERTIFICATE_VALIDITY: std::time::Duration = std::time::Duration::from_secs(365 * 24 * 3600);
+
+fn invalid_data<E>(err: E) -> std::io::Error
+where
+    E: std::fmt::Display,
+{
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
+}
+
+/// Load certificate chain and private key (PEM files) of server
+pub fn load_acceptor(
+    cert: &std::path::Path,
+    key: &std::path::Path,
+) -> Result<tokio_rustls::TlsAcceptor, std::io::Error> {
+    let certs = tokio_rustls::rustls::pki_types::CertificateDer::pem_file_iter(cert)
+        .and_then(|x| x.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| invalid_data(format!("certificate {:?} - {}", cert, err)))?;
+    let key = tokio_rustls::rustls::pki_types::PrivateKeyDer::from_pem_file(key)
+        .map_err(|err| invalid_data(format!("private key {:?} - {}", key, err)))?;
+
+    let config = tokio_rustls::rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(invalid_data)?;
+
+    Ok(tokio_rustls::TlsAcceptor::from(std::sync::Arc::new(config)))
+}
+
+/// Generate self-signed certificate (valid for localhost and given address if specified) and its private key
+/// for play in local network
+pub fn generate_self_signed(
+    cert: &std::path::Path,
+    key: &std::path::Path,
+    ip: std::net::IpAddr,
+) -> Result<(), std::io::Error> {
+    use p256::pkcs8::EncodePrivateKey;
+    use std::io::Write;
+    use std::str::FromStr;
+    use x509_cert::builder::Builder;
+    use x509_cert::der::EncodePem;
+
+    let signing_key = p256::ecdsa::SigningKey::random(&mut rand::rngs::OsRng);
+    let public_key =
+        x509_cert::spki::SubjectPublicKeyInfoOwned::from_key(*signing_key.verifying_key())
+            .map_err(invalid_data)?;
+
+    // Serial number has to be positive
+    let mut serial: [u8; 16] = rand::random();
+    serial[0] &= 0x7f;
+
+    let subject =
+        x509_cert::name::Name::from_str("CN=noughts-and-crosses").map_err(invalid_data)?;
+    let profile = x509_cert::builder::Profile::Leaf {
+        issuer: subject.clone(),
+        enable_key_agreement: false,
+        enable_key_encipherment: false,
+    };
+
+    let mut builder = x509_cert::builder::CertificateBuilder::new(
+        profile,
+        x509_cert::serial_number::SerialNumber::new(&serial).map_err(invalid_data)?,
+        x509_cert::time::Validity::from_now(CERTIFICATE_VALIDITY).map_err(invalid_data)?,
+        subject,
+        public_key,
+        &signing_key,
+    )
+    .map_err(invalid_data)?;
+
+    let mut names = vec![x509_cert::ext::pkix::name::GeneralName::DnsName(
+        x509_cert::der::asn1::Ia5String::new("localhost").map_err(invalid_data)?,
+    )];
+    // Server listening on all interfaces has no single address
+    if !ip.is_unspecified() {
+        let ip = match ip {
+            std::net::IpAddr::V4(ip) => ip.octets().to_vec(),
+            std::net::IpAddr::V6(ip) => ip.octets().to_vec(),
+        };
+        names.push(x509_cert::ext::pkix::name::GeneralName::IpAddress(
+            x509_cert::der::asn1::OctetString::new(ip).map_err(invalid_data)?,
+        ));
+    }
+    builder
+        .add_extension(&x509_cert::ext::pkix::SubjectAltName(names))
+        .map_err(invalid_data)?;
+
+    let certificate = builder
+        .build::<p256::ecdsa::DerSignature>()
+        .map_err(invalid_data)?;
+
+    std::fs::write(
+        cert,
+        certificate
+            .to_pem(x509_cert::der::pem::LineEnding::LF)
+            .map_err(invalid_data)?,
+    )?;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // Private key is readable only by owner
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(key)?;
+    file.write_all(
+        signing_key
+            .to_pkcs8_pem(x509_cert::der::pem::LineEnding::LF)
+            .map_err(invalid_data)?
+            .as_bytes(),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn generated_certificate_is_loaded() {
+        let dir = std::env::temp_dir();
+        let cert = dir.join(format!("cert-test-{}.pem", std::process::id()));
+        let key = dir.join(format!("key-test-{}.pem", std::process::id()));
+
+        generate_self_signed(&cert, &key, std::net::IpAddr::from([127, 0, 0, 1])).unwrap();
+        let acceptor = load_acceptor(&cert, &key);
+
+        let _ = std::fs::remove_file(&cert);
+        let _ = std::fs::remove_file(&key);
+
+        assert!(acceptor.is_ok());
+        assert!(load_acceptor(&cert, &key).is_err());
+    }
+}
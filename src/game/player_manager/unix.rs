@@ -0,0 +1,118 @@
+use super::{ClientId, MsgFromPlayer};
+
+/// Socket file of listener - it is removed when listener is stopped (including end of server)
+struct SocketFile(std::path::PathBuf);
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+async fn connection_listener(
+    listener: tokio::net::UnixListener,
+    socket_file: SocketFile,
+    accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    tx: tokio::sync::mpsc::Sender<MsgFromPlayer<super::tcp::Connection, String>>,
+) {
+    let _socket_file = socket_file;
+    let mut next_client_id = 0;
+
+    loop {
+        let (stream, _) = listener.accept().await.unwrap();
+        let client_id = ClientId(next_client_id);
+        next_client_id += 1;
+
+        log::info!("New Unix socket connection {}", client_id);
+
+        super::tcp::accept_connection(client_id, Box::new(stream), accounts.clone(), tx.clone())
+            .await;
+    }
+}
+
+/// Remove socket left by previous run of server - socket of running server and other files
+/// are kept
+fn remove_stale_socket(path: &std::path::Path) -> Result<(), std::io::Error> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            match std::os::unix::net::UnixStream::connect(path) {
+                Ok(_) => Err(std::io::Error::new(
+                    std::io::ErrorKind::AddrInUse,
+                    "socket is used by running server",
+                )),
+                Err(_) => std::fs::remove_file(path),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Player manager of clients connected by Unix domain socket - clients use same protocol as
+/// TCP clients
+pub fn from_path(
+    path: &std::path::Path,
+    accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+) -> Result<super::tcp::PlayerManager, std::io::Error> {
+    remove_stale_socket(path)?;
+    let listener = tokio::net::UnixListener::bind(path)?;
+    let socket_file = SocketFile(path.to_owned());
+
+    log::info!("Unix socket player manager running on path: {:?}", path);
+
+    Ok(super::tcp::PlayerManager::with_listener(move |tx| {
+        connection_listener(listener, socket_file, accounts, tx)
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_stale_socket_is_removed() {
+        let path = std::env::temp_dir().join(format!("unix-socket-test-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // Socket of running server is kept
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        assert_eq!(
+            remove_stale_socket(&path).map_err(|err| err.kind()),
+            Err(std::io::ErrorKind::AddrInUse)
+        );
+        assert!(path.exists());
+
+        // Socket file is left after server stopped without cleanup
+        drop(listener);
+        assert!(remove_stale_socket(&path).is_ok());
+        assert!(!path.exists());
+
+        // Other files are kept
+        std::fs::write(&path, "data").unwrap();
+        assert!(remove_stale_socket(&path).is_ok());
+        assert!(path.exists());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn socket_is_removed_with_listener() {
+        let path = std::env::temp_dir().join(format!("unix-socket-drop-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let (tx, _rx) = tokio::sync::mpsc::channel(1);
+        let task = tokio::spawn(connection_listener(
+            listener,
+            SocketFile(path.clone()),
+            None,
+            tx,
+        ));
+        assert!(path.exists());
+
+        task.abort();
+        let _ = task.await;
+        assert!(!path.exists());
+    }
+}
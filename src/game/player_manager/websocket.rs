@@ -0,0 +1,389 @@
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+
+use super::{AuthCommand, ClientId, MsgFromPlayer, Role};
+
+type WebSocket = tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>;
+
+/// Accepted WebSocket connection of client
+pub struct Connection {
+    stream: WebSocket,
+    /// Name of account of logged in client
+    account: Option<String>,
+}
+
+pub struct Player {
+    role: Role,
+    nickname: Option<String>,
+    /// Name of account of logged in client
+    account: Option<String>,
+    client_id: ClientId,
+    tx_client: tokio::sync::mpsc::Sender<String>,
+}
+
+async fn player_communication(
+    client_id: ClientId,
+    stream: WebSocket,
+    tx_game: tokio::sync::mpsc::Sender<MsgFromPlayer<Connection, String>>,
+    mut rx_client: tokio::sync::mpsc::Receiver<String>,
+) {
+    let (mut writer, mut reader) = stream.split();
+
+    loop {
+        tokio::select! {
+            // Reading input from client
+            msg = reader.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    let _ = tx_game.send(MsgFromPlayer::Msg(client_id, text.to_string())).await;
+                }
+                // Connection closed
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                    let _ = tx_game.send(MsgFromPlayer::Leave(client_id)).await;
+                    return;
+                }
+                // Pings are answered by library and binary messages are not used
+                Some(Ok(_)) => (),
+            },
+            // Sending message to client
+            msg = rx_client.recv() => {
+                match msg {
+                    Some(msg) => {
+                        let _ = writer.send(Message::Text(msg.into())).await;
+                    }
+                    // Player was dropped by game - close connection
+                    None => {
+                        let _ = writer.close().await;
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Player {
+    pub fn new(
+        client_id: ClientId,
+        role: Role,
+        connection: Connection,
+        tx_game: tokio::sync::mpsc::Sender<MsgFromPlayer<Connection, String>>,
+    ) -> Player {
+        let (tx_client, rx_client) = tokio::sync::mpsc::channel(5);
+
+        tokio::spawn(player_communication(
+            client_id,
+            connection.stream,
+            tx_game,
+            rx_client,
+        ));
+
+        Player {
+            role,
+            nickname: None,
+            account: connection.account,
+            client_id,
+            tx_client,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl super::PlayerTrait for Player {
+    type FieldRepresentation = super::super::converters::RenderedPlayboard;
+
+    async fn send_msg_to_player(&mut self, msg: super::MsgToPlayer<Self::FieldRepresentation>) {
+        match serde_json::to_string(&msg) {
+            Ok(text) => {
+                let _ = self.tx_client.send(text).await;
+            }
+            Err(err) => log::error!("Unable to encode message for {}: {}", self.client_id, err),
+        }
+    }
+
+    fn get_role(&self) -> Role {
+        self.role
+    }
+
+    fn set_role(&mut self, role: Role) {
+        self.role = role;
+    }
+
+    fn get_nickname(&self) -> Option<&str> {
+        self.nickname.as_deref()
+    }
+
+    fn set_nickname(&mut self, nickname: String) {
+        self.nickname = Some(nickname);
+    }
+
+    fn get_account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    fn get_client_id(&self) -> ClientId {
+        self.client_id
+    }
+}
+
+pub struct PlayerManager {
+    tx: tokio::sync::mpsc::Sender<MsgFromPlayer<Connection, String>>,
+    rx: tokio::sync::mpsc::Receiver<MsgFromPlayer<Connection, String>>,
+}
+
+/// Let client log in, register new account or continue as guest
+async fn authenticate(
+    stream: &mut WebSocket,
+    client_id: ClientId,
+    accounts: &super::super::accounts::AccountStore,
+) -> Option<Option<String>> {
+    let _ = stream
+        .send(Message::Text(r#"{"type":"authenticate"}"#.into()))
+        .await;
+
+    loop {
+        let text = match stream.next().await {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return None,
+            Some(Ok(_)) => continue,
+        };
+
+        let result = match serde_json::from_str::<AuthCommand>(&text) {
+            Ok(command) => command.authenticate(accounts).await,
+            Err(_) => {
+                let _ = stream
+                    .send(Message::Text(r#"{"type":"invalid_input"}"#.into()))
+                    .await;
+                continue;
+            }
+        };
+
+        match result {
+            Ok(account) => {
+                if let Some(name) = &account {
+                    log::info!("Client {} logged in as {}", client_id, name);
+                }
+                return Some(account);
+            }
+            Err(err) => {
+                log::info!("Authentication of client {} failed: {}", client_id, err);
+
+                let text = serde_json::json!({
+                    "type": "authentication_failed",
+                    "data": err.to_string(),
+                });
+                let _ = stream.send(Message::Text(text.to_string().into())).await;
+            }
+        }
+    }
+}
+
+/// Finish WebSocket handshake (and authentication) of new client
+async fn accept_connection(
+    client_id: ClientId,
+    stream: tokio::net::TcpStream,
+    accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    tx: tokio::sync::mpsc::Sender<MsgFromPlayer<Connection, String>>,
+) {
+    let mut stream = match tokio_tungstenite::accept_async(stream).await {
+        Ok(x) => x,
+        Err(err) => {
+            log::info!(
+                "WebSocket handshake of client {} failed: {}",
+                client_id,
+                err
+            );
+            return;
+        }
+    };
+
+    let account = match &accounts {
+        Some(accounts) => match authenticate(&mut stream, client_id, accounts).await {
+            Some(x) => x,
+            None => return,
+        },
+        None => None,
+    };
+
+    let connection = Connection { stream, account };
+    let _ = tx.send(MsgFromPlayer::Join(client_id, connection)).await;
+}
+
+async fn connection_listener(
+    listener: tokio::net::TcpListener,
+    accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    tx: tokio::sync::mpsc::Sender<MsgFromPlayer<Connection, String>>,
+) {
+    let mut next_client_id = 0;
+
+    loop {
+        let (stream, address) = listener.accept().await.unwrap();
+        let address = super::tcp::client_address(address);
+        let client_id = ClientId(next_client_id);
+        next_client_id += 1;
+
+        log::info!("New WebSocket connection {} from {}", client_id, address);
+
+        tokio::spawn(accept_connection(
+            client_id,
+            stream,
+            accounts.clone(),
+            tx.clone(),
+        ));
+    }
+}
+
+impl PlayerManager {
+    pub fn new(
+        listener: tokio::net::TcpListener,
+        accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    ) -> Self {
+        log::info!(
+            "WebSocket player manager running on address: {}",
+            listener.local_addr().unwrap()
+        );
+        super::tcp::log_connection_hints(&listener);
+
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
+
+        tokio::spawn(connection_listener(listener, accounts, tx.clone()));
+
+        PlayerManager { tx, rx }
+    }
+
+    pub async fn from_socket_address(
+        addr: std::net::SocketAddr,
+        accounts: Option<std::sync::Arc<super::super::accounts::AccountStore>>,
+    ) -> Result<PlayerManager, std::io::Error> {
+        let listener = super::tcp::bind_listener(addr)?;
+        Ok(Self::new(listener, accounts))
+    }
+}
+
+#[async_trait::async_trait]
+impl super::PlayerManagerTrait for PlayerManager {
+    type NewPlayerData = Connection;
+    type NewPlayer = Player;
+    type PlayerMsg = String;
+
+    fn create_new_player(
+        &self,
+        client_id: ClientId,
+        role: Role,
+        player_data: Self::NewPlayerData,
+    ) -> Self::NewPlayer {
+        Player::new(client_id, role, player_data, self.tx.clone())
+    }
+
+    async fn receive_new_message(&mut self) -> MsgFromPlayer<Self::NewPlayerData, Self::PlayerMsg> {
+        return self.rx.recv().await.unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{MsgToPlayer, PlayerManagerTrait, PlayerTrait};
+    use super::*;
+
+    async fn connect(
+        accounts: Option<std::sync::Arc<super::super::super::accounts::AccountStore>>,
+    ) -> (
+        PlayerManager,
+        tokio_tungstenite::WebSocketStream<
+            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+        >,
+    ) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let player_manager = PlayerManager::new(listener, accounts);
+        let (client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        (player_manager, client)
+    }
+
+    async fn receive_text<S>(client: &mut S) -> String
+    where
+        S: futures_util::Stream<Item = tokio_tungstenite::tungstenite::Result<Message>> + Unpin,
+    {
+        match client.next().await {
+            Some(Ok(Message::Text(text))) => text.to_string(),
+            x => panic!("Unexpected message {:?}", x),
+        }
+    }
+
+    #[tokio::test]
+    async fn messages_are_exchanged_as_json() {
+        let (mut player_manager, mut client) = connect(None).await;
+
+        let connection = match player_manager.receive_new_message().await {
+            MsgFromPlayer::Join(ClientId(0), x) => x,
+            _ => panic!("Client did not join"),
+        };
+        let mut player = player_manager.create_new_player(ClientId(0), Role::Guest, connection);
+        assert_eq!(player.get_account(), None);
+
+        player.send_msg_to_player(MsgToPlayer::WelcomePlayer).await;
+        assert_eq!(
+            receive_text(&mut client).await,
+            r#"{"type":"welcome_player"}"#
+        );
+
+        client
+            .send(Message::Text(
+                r#"{"type":"move","row":1,"column":2}"#.into(),
+            ))
+            .await
+            .unwrap();
+        assert!(matches!(
+            player_manager.receive_new_message().await,
+            MsgFromPlayer::Msg(ClientId(0), msg) if msg == r#"{"type":"move","row":1,"column":2}"#
+        ));
+
+        client.close(None).await.unwrap();
+        assert!(matches!(
+            player_manager.receive_new_message().await,
+            MsgFromPlayer::Leave(ClientId(0))
+        ));
+    }
+
+    #[tokio::test]
+    async fn client_logs_in_before_join() {
+        let path = std::env::temp_dir().join(format!("websocket-accounts-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let accounts =
+            std::sync::Arc::new(super::super::super::accounts::AccountStore::open(&path).unwrap());
+        accounts.register("alice", "password123").await.unwrap();
+
+        let (mut player_manager, mut client) = connect(Some(accounts)).await;
+        assert_eq!(
+            receive_text(&mut client).await,
+            r#"{"type":"authenticate"}"#
+        );
+
+        client
+            .send(Message::Text(
+                r#"{"type":"login","name":"alice","password":"wrong password"}"#.into(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            receive_text(&mut client).await,
+            r#"{"data":"invalid name or password","type":"authentication_failed"}"#
+        );
+
+        client
+            .send(Message::Text(
+                r#"{"type":"login","name":"alice","password":"password123"}"#.into(),
+            ))
+            .await
+            .unwrap();
+
+        let connection = match player_manager.receive_new_message().await {
+            MsgFromPlayer::Join(ClientId(0), x) => x,
+            _ => panic!("Client did not join"),
+        };
+        let player = player_manager.create_new_player(ClientId(0), Role::Guest, connection);
+        assert_eq!(player.get_account(), Some("alice"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,296 @@
+/// Rating of account without any rated game
+pub const INITIAL_RATING: i32 = 1500;
+/// Maximal change of rating after one game
+const K_FACTOR: f64 = 32.0;
+/// Number of accounts in leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Configuration of game with own ratings
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Pool {
+    /// Size of playboard edge
+    pub size: usize,
+    /// Number of fields in line required for win
+    pub win_length: usize,
+}
+
+impl std::fmt::Display for Pool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}/{}", self.size, self.size, self.win_length)
+    }
+}
+
+impl std::str::FromStr for Pool {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (size, win_length) = s.split_once('/').ok_or(())?;
+        let (size, _) = size.split_once('x').ok_or(())?;
+
+        Ok(Pool {
+            size: size.parse().map_err(|_| ())?,
+            win_length: win_length.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+/// Rating of account in pool
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Rating {
+    rating: i32,
+    games: usize,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating {
+            rating: INITIAL_RATING,
+            games: 0,
+        }
+    }
+}
+
+/// New rating of account after game
+#[derive(Debug, PartialEq, Clone, Copy, serde::Serialize)]
+pub struct RatingChange {
+    pub rating: i32,
+    pub change: i32,
+}
+
+/// Row of leaderboard
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+pub struct LeaderboardEntry {
+    pub account: String,
+    pub rating: i32,
+    pub games: usize,
+}
+
+/// Compute Elo ratings after game - score is 1 for win of first player, 0.5 for draw and 0 for loss
+fn compute_elo(first: i32, second: i32, score: f64) -> (i32, i32) {
+    let expected = 1.0 / (1.0 + 10f64.powf(f64::from(second - first) / 400.0));
+    let change = (K_FACTOR * (score - expected)).round() as i32;
+
+    (first + change, second - change)
+}
+
+/// Elo ratings of accounts saved in local file
+///
+/// Each line of file contains pool, name of account, rating and number of games separated by space.
+pub struct RatingStore {
+    path: std::path::PathBuf,
+    pools: std::sync::Mutex<
+        std::collections::HashMap<Pool, std::collections::HashMap<String, Rating>>,
+    >,
+    /// Saves of file are done one by one in order of games
+    save_lock: tokio::sync::Mutex<()>,
+}
+
+impl RatingStore {
+    /// Load ratings from file (file is created after first rated game)
+    pub fn open<P>(path: P) -> std::io::Result<RatingStore>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(x) => x,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        let mut pools: std::collections::HashMap<Pool, std::collections::HashMap<String, Rating>> =
+            std::collections::HashMap::new();
+
+        for line in content.lines() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            if let [pool, account, rating, games] = words.as_slice() {
+                if let (Ok(pool), Ok(rating), Ok(games)) =
+                    (pool.parse(), rating.parse(), games.parse())
+                {
+                    pools
+                        .entry(pool)
+                        .or_default()
+                        .insert(account.to_string(), Rating { rating, games });
+                }
+            }
+        }
+
+        Ok(RatingStore {
+            path: path.as_ref().to_owned(),
+            pools: std::sync::Mutex::new(pools),
+            save_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Get current rating of account in pool
+    pub fn get(&self, pool: &Pool, account: &str) -> i32 {
+        self.pools
+            .lock()
+            .unwrap()
+            .get(pool)
+            .and_then(|accounts| accounts.get(account))
+            .copied()
+            .unwrap_or_default()
+            .rating
+    }
+
+    /// Update ratings of accounts after game and save them
+    pub async fn record_game(
+        &self,
+        pool: &Pool,
+        first: &str,
+        second: &str,
+        score: f64,
+    ) -> std::io::Result<(RatingChange, RatingChange)> {
+        let _save_guard = self.save_lock.lock().await;
+        let (changes, content) = self.update(pool, first, second, score);
+
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || Self::save(&path, content)).await??;
+
+        Ok(changes)
+    }
+
+    /// Update ratings of accounts in memory and get new content of file
+    fn update(
+        &self,
+        pool: &Pool,
+        first: &str,
+        second: &str,
+        score: f64,
+    ) -> ((RatingChange, RatingChange), String) {
+        let mut pools = self.pools.lock().unwrap();
+        let accounts = pools.entry(*pool).or_default();
+
+        let first_rating = accounts.get(first).copied().unwrap_or_default();
+        let second_rating = accounts.get(second).copied().unwrap_or_default();
+
+        let (first_new, second_new) = compute_elo(first_rating.rating, second_rating.rating, score);
+
+        accounts.insert(
+            first.to_owned(),
+            Rating {
+                rating: first_new,
+                games: first_rating.games + 1,
+            },
+        );
+        accounts.insert(
+            second.to_owned(),
+            Rating {
+                rating: second_new,
+                games: second_rating.games + 1,
+            },
+        );
+
+        let changes = (
+            RatingChange {
+                rating: first_new,
+                change: first_new - first_rating.rating,
+            },
+            RatingChange {
+                rating: second_new,
+                change: second_new - second_rating.rating,
+            },
+        );
+
+        (changes, Self::to_text(&pools))
+    }
+
+    /// Get best accounts of pool
+    pub fn leaderboard(&self, pool: &Pool) -> Vec<LeaderboardEntry> {
+        let pools = self.pools.lock().unwrap();
+
+        let mut entries: Vec<LeaderboardEntry> = pools
+            .get(pool)
+            .map(|accounts| {
+                accounts
+                    .iter()
+                    .map(|(account, rating)| LeaderboardEntry {
+                        account: account.clone(),
+                        rating: rating.rating,
+                        games: rating.games,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        entries.sort_by(|a, b| b.rating.cmp(&a.rating).then(a.account.cmp(&b.account)));
+        entries.truncate(LEADERBOARD_SIZE);
+
+        entries
+    }
+
+    /// Format ratings as content of file
+    fn to_text(
+        pools: &std::collections::HashMap<Pool, std::collections::HashMap<String, Rating>>,
+    ) -> String {
+        pools
+            .iter()
+            .flat_map(|(pool, accounts)| {
+                accounts.iter().map(move |(account, rating)| {
+                    format!("{} {} {} {}\n", pool, account, rating.rating, rating.games)
+                })
+            })
+            .collect()
+    }
+
+    /// Rewrite file by ratings
+    fn save(path: &std::path::Path, content: String) -> std::io::Result<()> {
+        // File is replaced at once, so it is not corrupted by crash during writing
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, content)?;
+        std::fs::rename(&tmp_path, path)
+    }
+}
+
+/// Ratings used by one game
+#[derive(Clone)]
+pub struct RatedPool {
+    pub store: std::sync::Arc<RatingStore>,
+    pub pool: Pool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn elo_update() {
+        assert_eq!(compute_elo(1500, 1500, 1.0), (1516, 1484));
+        assert_eq!(compute_elo(1500, 1500, 0.5), (1500, 1500));
+        // Win of stronger player changes ratings less
+        assert_eq!(compute_elo(1700, 1500, 1.0), (1708, 1492));
+    }
+
+    #[tokio::test]
+    async fn ratings_are_saved() {
+        let path = std::env::temp_dir().join(format!("ratings-test-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let pool = Pool {
+            size: 5,
+            win_length: 4,
+        };
+        let other_pool = Pool {
+            size: 3,
+            win_length: 3,
+        };
+
+        let store = RatingStore::open(&path).unwrap();
+        store.record_game(&pool, "alice", "bob", 1.0).await.unwrap();
+
+        let store = RatingStore::open(&path).unwrap();
+        assert_eq!(store.get(&pool, "alice"), 1516);
+        assert_eq!(store.get(&other_pool, "alice"), INITIAL_RATING);
+        assert_eq!(
+            store.leaderboard(&pool)[0],
+            LeaderboardEntry {
+                account: "alice".to_owned(),
+                rating: 1516,
+                games: 1
+            }
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
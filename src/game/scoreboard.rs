@@ -0,0 +1,158 @@
+use super::player_manager::ClientId;
+
+/// Score of player in current match
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct Score {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+}
+
+/// Enum for state of match
+#[derive(Debug, PartialEq, Eq)]
+pub enum MatchState {
+    /// More games have to be played
+    InProgress,
+    /// Match was won by client
+    Won(ClientId),
+    /// Match ended and nobody win
+    Drawn,
+}
+
+/// Scoreboard counting results of games in match
+///
+/// Results are kept per client, so they are not affected by swap of sides.
+pub struct Scoreboard {
+    /// Number of games in match - None for endless match
+    match_length: Option<usize>,
+    wins: std::collections::HashMap<ClientId, usize>,
+    draws: usize,
+}
+
+impl Scoreboard {
+    pub fn new(match_length: Option<usize>) -> Self {
+        Scoreboard {
+            match_length,
+            wins: std::collections::HashMap::with_capacity(2),
+            draws: 0,
+        }
+    }
+
+    /// Save game won by client
+    pub fn add_win(&mut self, winner: ClientId) {
+        *self.wins.entry(winner).or_insert(0) += 1;
+    }
+
+    /// Save game where nobody win
+    pub fn add_draw(&mut self) {
+        self.draws += 1;
+    }
+
+    /// Move results of client to its new connection
+    pub fn replace_client(&mut self, old: ClientId, new: ClientId) {
+        if let Some(wins) = self.wins.remove(&old) {
+            self.wins.insert(new, wins);
+        }
+    }
+
+    /// Start new match
+    pub fn reset(&mut self) {
+        self.wins.clear();
+        self.draws = 0;
+    }
+
+    /// Number of games played in current match
+    pub fn games_played(&self) -> usize {
+        self.wins.values().sum::<usize>() + self.draws
+    }
+
+    /// Get score of client playing against opponent
+    pub fn get_score(&self, client_id: ClientId, opponent: ClientId) -> Score {
+        Score {
+            wins: self.wins.get(&client_id).copied().unwrap_or(0),
+            losses: self.wins.get(&opponent).copied().unwrap_or(0),
+            draws: self.draws,
+        }
+    }
+
+    /// Get state of match between two clients
+    pub fn get_match_state(&self, first: ClientId, second: ClientId) -> MatchState {
+        let match_length = match self.match_length {
+            Some(x) => x,
+            None => return MatchState::InProgress,
+        };
+
+        let score = self.get_score(first, second);
+        let remaining = match_length.saturating_sub(self.games_played());
+
+        if score.wins > score.losses + remaining {
+            MatchState::Won(first)
+        } else if score.losses > score.wins + remaining {
+            MatchState::Won(second)
+        } else if remaining == 0 {
+            MatchState::Drawn
+        } else {
+            MatchState::InProgress
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FIRST: ClientId = ClientId(0);
+    const SECOND: ClientId = ClientId(1);
+
+    #[test]
+    fn endless_match() {
+        let mut scoreboard = Scoreboard::new(None);
+
+        for _ in 0..5 {
+            scoreboard.add_win(FIRST);
+        }
+
+        assert_eq!(
+            scoreboard.get_match_state(FIRST, SECOND),
+            MatchState::InProgress
+        );
+    }
+
+    #[test]
+    fn match_clinched_before_end() {
+        let mut scoreboard = Scoreboard::new(Some(5));
+
+        scoreboard.add_win(SECOND);
+        scoreboard.add_draw();
+        scoreboard.add_win(SECOND);
+        assert_eq!(
+            scoreboard.get_match_state(FIRST, SECOND),
+            MatchState::InProgress
+        );
+
+        scoreboard.add_win(SECOND);
+        assert_eq!(
+            scoreboard.get_match_state(FIRST, SECOND),
+            MatchState::Won(SECOND)
+        );
+        assert_eq!(
+            scoreboard.get_score(FIRST, SECOND),
+            Score {
+                wins: 0,
+                losses: 3,
+                draws: 1
+            }
+        );
+    }
+
+    #[test]
+    fn drawn_match() {
+        let mut scoreboard = Scoreboard::new(Some(3));
+
+        scoreboard.add_win(FIRST);
+        scoreboard.add_win(SECOND);
+        scoreboard.add_draw();
+
+        assert_eq!(scoreboard.get_match_state(FIRST, SECOND), MatchState::Drawn);
+    }
+}
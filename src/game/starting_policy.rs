@@ -0,0 +1,153 @@
+use rand::{Rng, SeedableRng};
+
+use super::player_manager::ClientId;
+use super::PlayerId;
+
+/// Enum for policy selecting player starting new game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum StartingPolicy {
+    /// Players take turns in starting games
+    #[default]
+    Alternate,
+    /// Loser of previous game starts (alternate after draw)
+    LoserStarts,
+    /// Winner of previous game starts (alternate after draw)
+    WinnerStarts,
+    /// Starting player is chosen randomly from given seed
+    Random(u64),
+}
+
+/// Selector of player starting new game
+///
+/// History is kept per client, so it is not affected by swap of sides.
+pub struct StartingPlayerSelector {
+    policy: StartingPolicy,
+    rng: rand::rngs::StdRng,
+    /// Client who started previous game
+    last_starter: Option<ClientId>,
+    /// Winner of previous game - None in case of draw
+    last_winner: Option<ClientId>,
+}
+
+impl StartingPlayerSelector {
+    pub fn new(policy: StartingPolicy) -> Self {
+        let seed = match policy {
+            StartingPolicy::Random(seed) => seed,
+            _ => 0,
+        };
+
+        StartingPlayerSelector {
+            policy,
+            rng: rand::rngs::StdRng::seed_from_u64(seed),
+            last_starter: None,
+            last_winner: None,
+        }
+    }
+
+    /// Save result of finished game
+    pub fn game_finished(&mut self, winner: Option<ClientId>) {
+        self.last_winner = winner;
+    }
+
+    /// Move history of client to its new connection
+    pub fn replace_client(&mut self, old: ClientId, new: ClientId) {
+        for client_id in [&mut self.last_starter, &mut self.last_winner]
+            .into_iter()
+            .flatten()
+        {
+            if *client_id == old {
+                *client_id = new;
+            }
+        }
+    }
+
+    /// Forget previous games (e.g. when player leaves)
+    pub fn reset(&mut self) {
+        self.last_starter = None;
+        self.last_winner = None;
+    }
+
+    /// Select player starting new game
+    pub fn select(&mut self, circle: ClientId, cross: ClientId) -> PlayerId {
+        let side_of = |client_id: ClientId| match client_id == circle {
+            true => PlayerId::Circle,
+            false => PlayerId::Cross,
+        };
+
+        let last_starter = self
+            .last_starter
+            .filter(|client_id| *client_id == circle || *client_id == cross);
+
+        let starter = match (self.policy, last_starter, self.last_winner) {
+            (StartingPolicy::Random(_), _, _) => match self.rng.gen_bool(0.5) {
+                true => PlayerId::Circle,
+                false => PlayerId::Cross,
+            },
+            (_, None, _) => PlayerId::Circle,
+            (StartingPolicy::LoserStarts, Some(_), Some(winner)) => !side_of(winner),
+            (StartingPolicy::WinnerStarts, Some(_), Some(winner)) => side_of(winner),
+            (_, Some(last_starter), _) => !side_of(last_starter),
+        };
+
+        self.last_starter = Some(match starter {
+            PlayerId::Circle => circle,
+            PlayerId::Cross => cross,
+        });
+        self.last_winner = None;
+
+        starter
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CIRCLE: ClientId = ClientId(0);
+    const CROSS: ClientId = ClientId(1);
+
+    #[test]
+    fn alternate() {
+        let mut selector = StartingPlayerSelector::new(StartingPolicy::Alternate);
+
+        assert_eq!(selector.select(CIRCLE, CROSS), PlayerId::Circle);
+        selector.game_finished(Some(CIRCLE));
+        assert_eq!(selector.select(CIRCLE, CROSS), PlayerId::Cross);
+        selector.game_finished(None);
+        assert_eq!(selector.select(CIRCLE, CROSS), PlayerId::Circle);
+    }
+
+    #[test]
+    fn alternate_after_swap_of_sides() {
+        let mut selector = StartingPlayerSelector::new(StartingPolicy::Alternate);
+
+        assert_eq!(selector.select(CIRCLE, CROSS), PlayerId::Circle);
+        selector.game_finished(None);
+        // Client who started previous game plays for cross now
+        assert_eq!(selector.select(CROSS, CIRCLE), PlayerId::Circle);
+    }
+
+    #[test]
+    fn loser_and_winner_starts() {
+        let mut loser = StartingPlayerSelector::new(StartingPolicy::LoserStarts);
+        let mut winner = StartingPlayerSelector::new(StartingPolicy::WinnerStarts);
+
+        for selector in [&mut loser, &mut winner] {
+            assert_eq!(selector.select(CIRCLE, CROSS), PlayerId::Circle);
+            selector.game_finished(Some(CIRCLE));
+        }
+
+        assert_eq!(loser.select(CIRCLE, CROSS), PlayerId::Cross);
+        assert_eq!(winner.select(CIRCLE, CROSS), PlayerId::Circle);
+    }
+
+    #[test]
+    fn random_is_reproducible() {
+        let mut first = StartingPlayerSelector::new(StartingPolicy::Random(42));
+        let mut second = StartingPlayerSelector::new(StartingPolicy::Random(42));
+
+        for _ in 0..10 {
+            assert_eq!(first.select(CIRCLE, CROSS), second.select(CIRCLE, CROSS));
+        }
+    }
+}
@@ -0,0 +1,435 @@
+pub mod standings;
+
+use super::player_manager::{
+    room, ClientId, MsgFromPlayer, MsgToPlayer, PlayerManagerTrait, PlayerTrait, Role,
+};
+use standings::Outcome;
+
+/// Registered participant of tournament
+#[derive(Debug, PartialEq, Clone)]
+pub enum Participant {
+    /// Client logged in to account with given name
+    Human(String),
+    /// Built-in bot with given name
+    Bot(String),
+}
+
+impl Participant {
+    pub fn get_name(&self) -> &str {
+        match self {
+            Self::Human(name) | Self::Bot(name) => name,
+        }
+    }
+}
+
+/// Load participants from lines of file - "<account>" for human or "bot <name>" for bot
+///
+/// Empty lines and lines starting by "#" are skipped.
+pub fn parse_participants(content: &str) -> Result<Vec<Participant>, String> {
+    let mut participants: Vec<Participant> = Vec::new();
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let participant = match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            ["bot", name] => Participant::Bot(name.to_string()),
+            [name] => Participant::Human(name.to_string()),
+            _ => return Err(format!("invalid participant \"{}\"", line)),
+        };
+
+        super::nickname::validate(
+            participant.get_name(),
+            participants.iter().map(Participant::get_name),
+        )
+        .map_err(|err| format!("invalid name of participant \"{}\" ({:?})", line, err))?;
+
+        participants.push(participant);
+    }
+
+    match participants.len() {
+        0 | 1 => Err("tournament requires at least 2 participants".to_owned()),
+        _ => Ok(participants),
+    }
+}
+
+/// Settings of tournament
+pub struct Settings {
+    pub participants: Vec<Participant>,
+    pub format: standings::Format,
+    /// Maximal time of waiting for all participants before first round
+    pub start_timeout: std::time::Duration,
+}
+
+/// Game of running round
+struct Room<P, M>
+where
+    P: PlayerTrait,
+{
+    tx: tokio::sync::mpsc::Sender<MsgFromPlayer<room::Player<P>, M>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// Runner pairing connected participants and playing their games in rooms
+struct Runner<T, F>
+where
+    T: PlayerManagerTrait,
+{
+    player_manager: T,
+    create_room: F,
+    tournament: standings::Tournament,
+    participants: Vec<Participant>,
+    /// Connected participants which do not play game
+    idle: std::collections::HashMap<usize, T::NewPlayer>,
+    /// Participants of connected clients
+    clients: std::collections::HashMap<ClientId, usize>,
+    /// Games of current round by index of pairing
+    rooms: std::collections::HashMap<usize, Room<T::NewPlayer, T::PlayerMsg>>,
+    /// Pairings of clients which are in some room
+    locations: std::collections::HashMap<ClientId, usize>,
+    /// Clients whose opponent left running game
+    forfeit_winners: std::collections::HashSet<ClientId>,
+    tx_left: tokio::sync::mpsc::UnboundedSender<room::LeftPlayer<T::NewPlayer>>,
+    tx_results: tokio::sync::mpsc::UnboundedSender<super::GameRecord>,
+}
+
+impl<T, F> Runner<T, F>
+where
+    T: PlayerManagerTrait,
+    F: Fn(
+        room::PlayerManager<T::NewPlayer, T::PlayerMsg>,
+        tokio::sync::mpsc::UnboundedSender<super::GameRecord>,
+    ) -> tokio::task::JoinHandle<()>,
+{
+    /// Add connected participant to tournament
+    async fn add_participant(&mut self, participant: usize, mut player: T::NewPlayer) {
+        log::info!(
+            "Participant {} connected as client {}",
+            self.participants[participant].get_name(),
+            player.get_client_id()
+        );
+
+        player
+            .send_msg_to_player(MsgToPlayer::WelcomeTournament)
+            .await;
+        self.clients.insert(player.get_client_id(), participant);
+        self.idle.insert(participant, player);
+    }
+
+    async fn handle_join(&mut self, client_id: ClientId, new_player_data: T::NewPlayerData) {
+        let mut player =
+            self.player_manager
+                .create_new_player(client_id, Role::Guest, new_player_data);
+
+        let participant = self.participants.iter().position(|x| match x {
+            Participant::Human(name) => Some(name.as_str()) == player.get_account(),
+            Participant::Bot(_) => false,
+        });
+
+        match participant {
+            Some(x) if !self.clients.values().any(|y| *y == x) => {
+                self.add_participant(x, player).await
+            }
+            // Connection is closed by drop of player
+            _ => {
+                log::info!("Client {} rejected - not participant", client_id);
+                player.send_msg_to_player(MsgToPlayer::NotParticipant).await
+            }
+        }
+    }
+
+    async fn handle_msg(&mut self, client_id: ClientId, msg: T::PlayerMsg) {
+        if let Some(pairing) = self.locations.get(&client_id) {
+            if let Some(room) = self.rooms.get(pairing) {
+                let _ = room.tx.send(MsgFromPlayer::Msg(client_id, msg)).await;
+            }
+            return;
+        }
+
+        if let Some(player) = self
+            .clients
+            .get(&client_id)
+            .and_then(|x| self.idle.get_mut(x))
+        {
+            player
+                .send_msg_to_player(MsgToPlayer::WaitingForNextRound)
+                .await;
+        }
+    }
+
+    async fn handle_leave(&mut self, client_id: ClientId) {
+        let participant = match self.clients.remove(&client_id) {
+            Some(x) => x,
+            None => return,
+        };
+
+        log::info!(
+            "Participant {} disconnected",
+            self.participants[participant].get_name()
+        );
+        self.idle.remove(&participant);
+
+        // Participant leaving running game loses it
+        let pairing_index = match self.locations.get(&client_id) {
+            Some(x) if self.rooms.contains_key(x) => *x,
+            _ => return,
+        };
+        let pairing = self.tournament.get_current_round()[pairing_index];
+        let outcome = match pairing.first == participant {
+            true => Outcome::FirstForfeited,
+            false => Outcome::SecondForfeited,
+        };
+
+        self.forfeit_winners.extend(
+            self.locations
+                .iter()
+                .filter(|(id, x)| **x == pairing_index && **id != client_id)
+                .map(|(id, _)| *id),
+        );
+        self.finish_game(pairing_index, outcome);
+    }
+
+    /// Game of room finished
+    fn handle_record(&mut self, record: super::GameRecord) {
+        let pairing_index = match self.locations.get(&record.circle) {
+            Some(x) if self.rooms.contains_key(x) => *x,
+            _ => return,
+        };
+        let pairing = self.tournament.get_current_round()[pairing_index];
+
+        let outcome = match record
+            .winner
+            .and_then(|client_id| self.clients.get(&client_id))
+        {
+            Some(x) if *x == pairing.first => Outcome::FirstWon,
+            Some(_) => Outcome::SecondWon,
+            None => Outcome::Draw,
+        };
+
+        self.finish_game(pairing_index, outcome);
+    }
+
+    /// Save result and close room - players are returned by channel
+    fn finish_game(&mut self, pairing_index: usize, outcome: Outcome) {
+        let pairing = self.tournament.get_current_round()[pairing_index];
+
+        if let (Some(room), Some(second)) = (self.rooms.remove(&pairing_index), pairing.second) {
+            room.task.abort();
+
+            log::info!(
+                "Game {} - {} finished {:?}",
+                self.participants[pairing.first].get_name(),
+                self.participants[second].get_name(),
+                outcome
+            );
+        }
+
+        self.tournament.record(pairing_index, outcome);
+    }
+
+    /// Participant returned from closed room
+    async fn handle_returned(&mut self, mut player: T::NewPlayer) {
+        let client_id = player.get_client_id();
+        self.locations.remove(&client_id);
+
+        // Disconnected client is dropped
+        if let Some(participant) = self.clients.get(&client_id) {
+            player.set_role(Role::Guest);
+
+            if self.forfeit_winners.remove(&client_id) {
+                player
+                    .send_msg_to_player(MsgToPlayer::OpponentForfeited)
+                    .await;
+            }
+
+            self.idle.insert(*participant, player);
+        }
+    }
+
+    /// Pair participants and start games of next round
+    async fn start_round(&mut self) {
+        let pairings = self.tournament.pair_next_round().to_vec();
+        let round = self.tournament.get_rounds_count();
+
+        log::info!(
+            "Round {} of {} starts",
+            self.tournament.get_current_round_number(),
+            round
+        );
+
+        for player in self.idle.values_mut() {
+            player
+                .send_msg_to_player(MsgToPlayer::RoundStarted(
+                    self.tournament.get_current_round_number(),
+                    round,
+                ))
+                .await;
+        }
+
+        for (index, pairing) in pairings.into_iter().enumerate() {
+            let second = match pairing.second {
+                Some(x) => x,
+                None => {
+                    if let Some(player) = self.idle.get_mut(&pairing.first) {
+                        player.send_msg_to_player(MsgToPlayer::Bye).await;
+                    }
+                    continue;
+                }
+            };
+
+            match (
+                self.idle.contains_key(&pairing.first),
+                self.idle.contains_key(&second),
+            ) {
+                (true, true) => self.start_game(index, pairing.first, second).await,
+                (true, false) => {
+                    self.tournament.record(index, Outcome::SecondForfeited);
+                    let player = self.idle.get_mut(&pairing.first).unwrap();
+                    player
+                        .send_msg_to_player(MsgToPlayer::OpponentForfeited)
+                        .await;
+                }
+                (false, true) => {
+                    self.tournament.record(index, Outcome::FirstForfeited);
+                    let player = self.idle.get_mut(&second).unwrap();
+                    player
+                        .send_msg_to_player(MsgToPlayer::OpponentForfeited)
+                        .await;
+                }
+                (false, false) => self.tournament.record(index, Outcome::BothForfeited),
+            }
+        }
+    }
+
+    /// Move both participants of pairing to new room
+    async fn start_game(&mut self, pairing_index: usize, first: usize, second: usize) {
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
+        let task = (self.create_room)(room::PlayerManager::new(rx), self.tx_results.clone());
+
+        log::info!(
+            "Game {} - {} started",
+            self.participants[first].get_name(),
+            self.participants[second].get_name()
+        );
+
+        for participant in [first, second] {
+            let player = self.idle.remove(&participant).unwrap();
+            let client_id = player.get_client_id();
+
+            self.locations.insert(client_id, pairing_index);
+
+            let player = room::Player::new(player, self.tx_left.clone());
+            let _ = tx.send(MsgFromPlayer::Join(client_id, player)).await;
+        }
+
+        self.rooms.insert(pairing_index, Room { tx, task });
+    }
+
+    async fn broadcast_standings(&mut self) {
+        let standings = self.tournament.get_standings();
+
+        for player in self.idle.values_mut() {
+            player
+                .send_msg_to_player(MsgToPlayer::Standings(standings.clone()))
+                .await;
+        }
+    }
+}
+
+/// Run tournament of participants connected through player manager and return its results
+///
+/// Bots are created by create_bot with client ids counted down from maximal id.
+pub async fn run_tournament<T, F, B>(
+    player_manager: T,
+    settings: Settings,
+    create_room: F,
+    create_bot: B,
+) -> standings::Tournament
+where
+    T: PlayerManagerTrait,
+    F: Fn(
+        room::PlayerManager<T::NewPlayer, T::PlayerMsg>,
+        tokio::sync::mpsc::UnboundedSender<super::GameRecord>,
+    ) -> tokio::task::JoinHandle<()>,
+    B: Fn(&str) -> T::NewPlayerData,
+{
+    let (tx_left, mut rx_left) = tokio::sync::mpsc::unbounded_channel();
+    let (tx_results, mut rx_results) = tokio::sync::mpsc::unbounded_channel();
+
+    let names = settings
+        .participants
+        .iter()
+        .map(|x| x.get_name().to_owned())
+        .collect();
+
+    let mut runner = Runner {
+        player_manager,
+        create_room,
+        tournament: standings::Tournament::new(names, settings.format),
+        participants: settings.participants,
+        idle: std::collections::HashMap::new(),
+        clients: std::collections::HashMap::new(),
+        rooms: std::collections::HashMap::new(),
+        locations: std::collections::HashMap::new(),
+        forfeit_winners: std::collections::HashSet::new(),
+        tx_left,
+        tx_results,
+    };
+
+    for (index, participant) in runner.participants.clone().iter().enumerate() {
+        if let Participant::Bot(name) = participant {
+            let client_id = ClientId(usize::MAX - index);
+            let player =
+                runner
+                    .player_manager
+                    .create_new_player(client_id, Role::Guest, create_bot(name));
+            runner.add_participant(index, player).await;
+        }
+    }
+
+    // Waiting for participants
+    let start = tokio::time::Instant::now() + settings.start_timeout;
+    while runner.idle.len() < runner.participants.len() {
+        tokio::select! {
+            msg = runner.player_manager.receive_new_message() => match msg {
+                MsgFromPlayer::Join(client_id, new_player_data) => {
+                    runner.handle_join(client_id, new_player_data).await
+                }
+                MsgFromPlayer::Msg(client_id, msg) => runner.handle_msg(client_id, msg).await,
+                MsgFromPlayer::Leave(client_id) => runner.handle_leave(client_id).await,
+            },
+            _ = tokio::time::sleep_until(start) => break,
+        }
+    }
+
+    for _ in 0..runner.tournament.get_rounds_count() {
+        runner.start_round().await;
+
+        // Round ends when all participants returned from rooms
+        while !runner.rooms.is_empty() || !runner.locations.is_empty() {
+            tokio::select! {
+                msg = runner.player_manager.receive_new_message() => match msg {
+                    MsgFromPlayer::Join(client_id, new_player_data) => {
+                        runner.handle_join(client_id, new_player_data).await
+                    }
+                    MsgFromPlayer::Msg(client_id, msg) => runner.handle_msg(client_id, msg).await,
+                    MsgFromPlayer::Leave(client_id) => runner.handle_leave(client_id).await,
+                },
+                // Runner keeps senders, so channels are never closed
+                Some(record) = rx_results.recv() => runner.handle_record(record),
+                Some(left) = rx_left.recv() => runner.handle_returned(left.player).await,
+            }
+        }
+
+        runner.broadcast_standings().await;
+    }
+
+    log::info!("Tournament finished");
+
+    for player in runner.idle.values_mut() {
+        player.send_msg_to_player(MsgToPlayer::Goodbye).await;
+    }
+
+    runner.tournament
+}
@@ -0,0 +1,506 @@
+/// System generating pairings of rounds
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    /// Each participant plays once with each other participant
+    RoundRobin,
+    /// Given number of rounds, participants with similar score are paired
+    Swiss(usize),
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RoundRobin => write!(f, "round robin"),
+            Self::Swiss(rounds) => write!(f, "swiss system ({} rounds)", rounds),
+        }
+    }
+}
+
+/// Number of swiss rounds needed for single winner
+pub fn swiss_rounds(participants: usize) -> usize {
+    let mut rounds = 1;
+
+    while 1 << rounds < participants {
+        rounds += 1;
+    }
+
+    rounds
+}
+
+/// Result of pairing from view of first participant
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Outcome {
+    FirstWon,
+    SecondWon,
+    Draw,
+    /// First participant did not play game and lost
+    FirstForfeited,
+    /// Second participant did not play game and lost
+    SecondForfeited,
+    /// Nobody played game and both participants lost
+    BothForfeited,
+    /// First participant had no opponent and won
+    Bye,
+}
+
+impl Outcome {
+    /// Points of first and second participant (in half-points)
+    fn half_points(&self) -> (u32, u32) {
+        match self {
+            Self::FirstWon | Self::SecondForfeited | Self::Bye => (2, 0),
+            Self::SecondWon | Self::FirstForfeited => (0, 2),
+            Self::Draw => (1, 1),
+            Self::BothForfeited => (0, 0),
+        }
+    }
+
+    fn to_result_string(self) -> &'static str {
+        match self {
+            Self::FirstWon => "1-0",
+            Self::SecondWon => "0-1",
+            Self::Draw => "1/2-1/2",
+            Self::FirstForfeited => "-/+",
+            Self::SecondForfeited => "+/-",
+            Self::BothForfeited => "-/-",
+            Self::Bye => "bye",
+        }
+    }
+}
+
+/// Two participants of one game in round (indexes to list of participants)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Pairing {
+    pub first: usize,
+    /// None if first participant has bye
+    pub second: Option<usize>,
+    /// None while game is not finished
+    pub outcome: Option<Outcome>,
+}
+
+impl Pairing {
+    fn new(first: usize, second: Option<usize>) -> Self {
+        Pairing {
+            first,
+            second,
+            outcome: match second {
+                Some(_) => None,
+                None => Some(Outcome::Bye),
+            },
+        }
+    }
+
+    /// Opponent of participant and points scored by participant (in half-points)
+    fn result_of(&self, participant: usize) -> Option<(Option<usize>, u32)> {
+        let (first, second) = self.outcome?.half_points();
+
+        match participant {
+            x if x == self.first => Some((self.second, first)),
+            x if Some(x) == self.second => Some((Some(self.first), second)),
+            _ => None,
+        }
+    }
+}
+
+/// Row of tournament standings
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+pub struct Standing {
+    pub rank: usize,
+    pub name: String,
+    pub points: f64,
+    /// Sum of points of all opponents
+    pub buchholz: f64,
+    /// Sum of points of defeated opponents and half of points of drawn opponents
+    pub sonneborn_berger: f64,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl std::fmt::Display for Standing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}. {} - points {}, Buchholz {}, Sonneborn-Berger {} ({} wins, {} draws, {} losses)",
+            self.rank,
+            self.name,
+            self.points,
+            self.buchholz,
+            self.sonneborn_berger,
+            self.wins,
+            self.draws,
+            self.losses
+        )
+    }
+}
+
+/// Pairing of report with names of participants
+#[derive(serde::Serialize)]
+struct PairingReport<'a> {
+    first: &'a str,
+    second: Option<&'a str>,
+    result: Option<&'static str>,
+}
+
+/// Complete report of tournament
+#[derive(serde::Serialize)]
+struct Report<'a> {
+    format: String,
+    standings: Vec<Standing>,
+    rounds: Vec<Vec<PairingReport<'a>>>,
+}
+
+/// Pairings and results of tournament
+pub struct Tournament {
+    participants: Vec<String>,
+    format: Format,
+    rounds: Vec<Vec<Pairing>>,
+}
+
+impl Tournament {
+    pub fn new(participants: Vec<String>, format: Format) -> Self {
+        Tournament {
+            participants,
+            format,
+            rounds: Vec::new(),
+        }
+    }
+
+    /// Number of current round (starting from 1)
+    pub fn get_current_round_number(&self) -> usize {
+        self.rounds.len()
+    }
+
+    /// Total number of rounds of tournament
+    pub fn get_rounds_count(&self) -> usize {
+        match self.format {
+            // Participant with bye in each round plays in one more round
+            Format::RoundRobin => {
+                self.participants.len().saturating_sub(1) + self.participants.len() % 2
+            }
+            Format::Swiss(rounds) => rounds,
+        }
+    }
+
+    /// Pairings of current round
+    pub fn get_current_round(&self) -> &[Pairing] {
+        self.rounds.last().map(|x| x.as_slice()).unwrap_or_default()
+    }
+
+    /// Generate pairings of next round
+    pub fn pair_next_round(&mut self) -> &[Pairing] {
+        let round = match self.format {
+            Format::RoundRobin => self.pair_round_robin(),
+            Format::Swiss(_) => self.pair_swiss(),
+        };
+
+        self.rounds.push(round);
+        self.get_current_round()
+    }
+
+    /// Save result of pairing of current round
+    pub fn record(&mut self, pairing: usize, outcome: Outcome) {
+        if let Some(pairing) = self.rounds.last_mut().and_then(|x| x.get_mut(pairing)) {
+            pairing.outcome = Some(outcome);
+        }
+    }
+
+    /// Pairing by circle method - first participant is fixed, others rotate
+    fn pair_round_robin(&self) -> Vec<Pairing> {
+        let round = self.rounds.len();
+        let mut ids: Vec<Option<usize>> = (0..self.participants.len()).map(Some).collect();
+
+        // Participant paired with None has bye
+        if ids.len() % 2 == 1 {
+            ids.push(None);
+        }
+
+        let count = ids.len();
+        if count < 2 {
+            return Vec::new();
+        }
+        ids[1..].rotate_right(round % (count - 1));
+
+        (0..count / 2)
+            .filter_map(|index| {
+                let (mut first, mut second) = (ids[index], ids[count - 1 - index]);
+
+                // Fixed participant alternates sides
+                if index == 0 && round % 2 == 1 {
+                    std::mem::swap(&mut first, &mut second);
+                }
+
+                match (first, second) {
+                    (Some(first), second) => Some(Pairing::new(first, second)),
+                    (None, Some(second)) => Some(Pairing::new(second, None)),
+                    (None, None) => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Pairing of participants with similar score who did not play yet
+    fn pair_swiss(&self) -> Vec<Pairing> {
+        let points = self.get_half_points();
+        let mut order: Vec<usize> = (0..self.participants.len()).collect();
+        order.sort_by(|a, b| points[*b].cmp(&points[*a]).then(a.cmp(b)));
+
+        // Lowest ranked participant without bye gets bye
+        let bye = match order.len() % 2 {
+            1 => {
+                let index = order
+                    .iter()
+                    .rposition(|x| !self.had_bye(*x))
+                    .unwrap_or(order.len() - 1);
+                Some(Pairing::new(order.remove(index), None))
+            }
+            _ => None,
+        };
+
+        let mut round = Vec::with_capacity(order.len() / 2 + 1);
+
+        while !order.is_empty() {
+            let first = order.remove(0);
+            let index = order
+                .iter()
+                .position(|x| !self.played(first, *x))
+                .unwrap_or(0);
+            let second = order.remove(index);
+
+            round.push(Pairing::new(first, Some(second)));
+        }
+
+        round.extend(bye);
+        round
+    }
+
+    fn played(&self, first: usize, second: usize) -> bool {
+        self.rounds.iter().flatten().any(|pairing| {
+            (pairing.first == first && pairing.second == Some(second))
+                || (pairing.first == second && pairing.second == Some(first))
+        })
+    }
+
+    fn had_bye(&self, participant: usize) -> bool {
+        self.rounds
+            .iter()
+            .flatten()
+            .any(|pairing| pairing.first == participant && pairing.second.is_none())
+    }
+
+    /// Points of all participants (in half-points)
+    fn get_half_points(&self) -> Vec<u32> {
+        (0..self.participants.len())
+            .map(|participant| {
+                self.rounds
+                    .iter()
+                    .flatten()
+                    .filter_map(|pairing| pairing.result_of(participant))
+                    .map(|(_, points)| points)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Standings sorted by points, Buchholz and Sonneborn-Berger
+    ///
+    /// Forfeited games count as played for tiebreaks, byes are not counted.
+    pub fn get_standings(&self) -> Vec<Standing> {
+        let points = self.get_half_points();
+
+        let mut standings: Vec<Standing> = self
+            .participants
+            .iter()
+            .enumerate()
+            .map(|(participant, name)| {
+                let mut standing = Standing {
+                    rank: 0,
+                    name: name.clone(),
+                    points: f64::from(points[participant]) / 2.0,
+                    buchholz: 0.0,
+                    sonneborn_berger: 0.0,
+                    wins: 0,
+                    draws: 0,
+                    losses: 0,
+                };
+
+                for (opponent, scored) in self
+                    .rounds
+                    .iter()
+                    .flatten()
+                    .filter_map(|pairing| pairing.result_of(participant))
+                {
+                    match scored {
+                        2 => standing.wins += 1,
+                        1 => standing.draws += 1,
+                        _ => standing.losses += 1,
+                    }
+
+                    if let Some(opponent) = opponent {
+                        let opponent_points = f64::from(points[opponent]) / 2.0;
+                        standing.buchholz += opponent_points;
+                        standing.sonneborn_berger += opponent_points * f64::from(scored) / 2.0;
+                    }
+                }
+
+                standing
+            })
+            .collect();
+
+        let key = |x: &Standing| (x.points, x.buchholz, x.sonneborn_berger);
+        standings.sort_by(|a, b| {
+            b.points
+                .total_cmp(&a.points)
+                .then(b.buchholz.total_cmp(&a.buchholz))
+                .then(b.sonneborn_berger.total_cmp(&a.sonneborn_berger))
+                .then(a.name.cmp(&b.name))
+        });
+
+        // Participants equal in all tiebreaks share rank
+        for index in 0..standings.len() {
+            standings[index].rank =
+                match index > 0 && key(&standings[index - 1]) == key(&standings[index]) {
+                    true => standings[index - 1].rank,
+                    false => index + 1,
+                };
+        }
+
+        standings
+    }
+
+    /// Report of standings and all rounds in readable form
+    pub fn to_text_report(&self) -> String {
+        let mut report = format!(
+            "Tournament - {}, {} participants\n\nStandings\n",
+            self.format,
+            self.participants.len()
+        );
+
+        for standing in self.get_standings() {
+            report += &format!("{}\n", standing);
+        }
+
+        for (index, round) in self.rounds.iter().enumerate() {
+            report += &format!("\nRound {}\n", index + 1);
+
+            for pairing in round {
+                let result = pairing.outcome.map_or("*", Outcome::to_result_string);
+
+                report += &match pairing.second {
+                    Some(second) => format!(
+                        "{} - {} {}\n",
+                        self.participants[pairing.first], self.participants[second], result
+                    ),
+                    None => format!("{} {}\n", self.participants[pairing.first], result),
+                };
+            }
+        }
+
+        report
+    }
+
+    /// Report of standings and all rounds in JSON
+    pub fn to_json_report(&self) -> String {
+        let report = Report {
+            format: self.format.to_string(),
+            standings: self.get_standings(),
+            rounds: self
+                .rounds
+                .iter()
+                .map(|round| {
+                    round
+                        .iter()
+                        .map(|pairing| PairingReport {
+                            first: &self.participants[pairing.first],
+                            second: pairing.second.map(|x| self.participants[x].as_str()),
+                            result: pairing.outcome.map(Outcome::to_result_string),
+                        })
+                        .collect()
+                })
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&report).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(count: usize) -> Vec<String> {
+        (0..count).map(|x| format!("player{}", x)).collect()
+    }
+
+    #[test]
+    fn round_robin_pairs_everybody_once() {
+        let mut tournament = Tournament::new(names(5), Format::RoundRobin);
+        assert_eq!(tournament.get_rounds_count(), 5);
+
+        let mut games = std::collections::HashSet::new();
+        for _ in 0..tournament.get_rounds_count() {
+            let round = tournament.pair_next_round().to_vec();
+            assert_eq!(round.iter().filter(|x| x.second.is_none()).count(), 1);
+
+            for pairing in round.iter().filter(|x| x.second.is_some()) {
+                let pair = (
+                    pairing.first.min(pairing.second.unwrap()),
+                    pairing.first.max(pairing.second.unwrap()),
+                );
+                assert!(games.insert(pair));
+            }
+        }
+
+        assert_eq!(games.len(), 10);
+    }
+
+    #[test]
+    fn swiss_avoids_rematches_and_repeated_byes() {
+        let mut tournament = Tournament::new(names(3), Format::Swiss(3));
+
+        let mut byes = std::collections::HashSet::new();
+        for _ in 0..3 {
+            let round = tournament.pair_next_round().to_vec();
+            assert!(byes.insert(round.last().unwrap().first));
+
+            for (index, pairing) in round.iter().enumerate() {
+                if pairing.second.is_some() {
+                    tournament.record(index, Outcome::FirstWon);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn standings_with_tiebreaks() {
+        let mut tournament = Tournament::new(names(4), Format::Swiss(2));
+
+        // Round 1: player0 beats player1, player2 and player3 draw
+        tournament.pair_next_round();
+        tournament.record(0, Outcome::FirstWon);
+        tournament.record(1, Outcome::Draw);
+
+        // Round 2: player0 vs player2, player3 vs player1 - player1 forfeits
+        let round = tournament.pair_next_round().to_vec();
+        assert_eq!(round[0], Pairing::new(0, Some(2)));
+        assert_eq!(round[1], Pairing::new(3, Some(1)));
+        tournament.record(0, Outcome::Draw);
+        tournament.record(1, Outcome::SecondForfeited);
+
+        let standings = tournament.get_standings();
+        let ranking: Vec<(&str, usize, f64, f64)> = standings
+            .iter()
+            .map(|x| (x.name.as_str(), x.rank, x.points, x.buchholz))
+            .collect();
+
+        assert_eq!(
+            ranking,
+            vec![
+                ("player0", 1, 1.5, 1.0),
+                ("player3", 1, 1.5, 1.0),
+                ("player2", 3, 1.0, 3.0),
+                ("player1", 4, 0.0, 3.0),
+            ]
+        );
+        assert_eq!(standings[0].sonneborn_berger, 0.5);
+    }
+}
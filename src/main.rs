@@ -4,7 +4,7 @@ mod game;
 
 ///Get user arguments from cmd
 fn get_args() -> clap::ArgMatches {
-    clap::Command::new("Noughts and crosses")
+    let mut command = clap::Command::new("Noughts and crosses")
         .args(vec![
             clap::Arg::new("port")
                 .short('p')
@@ -12,6 +12,85 @@ fn get_args() -> clap::ArgMatches {
                 .required(false)
                 .help("Custom required port")
                 .value_parser(clap::value_parser!(u16)),
+            clap::Arg::new("bind")
+                .long("bind")
+                .required(false)
+                .help("IP address of listening sockets (IPv6 link-local address with scope, e.g. fe80::1%eth0)")
+                .value_parser(clap::value_parser!(game::player_manager::tcp::ListenAddress))
+                .conflicts_with_all(["interface", "bind_all"]),
+            clap::Arg::new("interface")
+                .long("interface")
+                .required(false)
+                .help("Name of network interface with address of listening sockets")
+                .conflicts_with("bind_all"),
+            clap::Arg::new("bind_all")
+                .long("bind-all")
+                .required(false)
+                .help("Listen on all network interfaces (IPv6 and IPv4)")
+                .action(clap::ArgAction::SetTrue),
+            clap::Arg::new("tls_cert")
+                .long("tls-cert")
+                .required(false)
+                .help("File with certificate chain (PEM) - TCP clients have to connect by TLS")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .requires("tls_key"),
+            clap::Arg::new("tls_key")
+                .long("tls-key")
+                .required(false)
+                .help("File with private key (PEM) of TLS certificate")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .requires("tls_cert"),
+            clap::Arg::new("generate_cert")
+                .long("generate-cert")
+                .required(false)
+                .help("Generate self-signed TLS certificate and key (if files do not exist) for play in local network")
+                .action(clap::ArgAction::SetTrue)
+                .requires("tls_cert"),
+            clap::Arg::new("websocket_port")
+                .long("websocket-port")
+                .required(false)
+                .help("Port for WebSocket clients playing own game alongside TCP clients")
+                .value_parser(clap::value_parser!(u16)),
+            clap::Arg::new("http_port")
+                .long("http-port")
+                .required(false)
+                .help("Port for HTTP clients playing own game alongside TCP clients")
+                .value_parser(clap::value_parser!(u16)),
+            clap::Arg::new("shared_game")
+                .long("shared-game")
+                .required(false)
+                .help("WebSocket, HTTP and Unix socket clients join game (or lobby) of TCP clients instead of own games")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("tournament"),
+            clap::Arg::new("bot")
+                .long("bot")
+                .required(false)
+                .help("Seat built-in bot with given name as one of players")
+                .conflicts_with_all(["rooms", "matchmaking", "tournament"]),
+            clap::Arg::new("local")
+                .long("local")
+                .required(false)
+                .help("Two players take turns at this terminal without networking")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "port",
+                    "websocket_port",
+                    "http_port",
+                    "accounts",
+                    "ratings",
+                    "rooms",
+                    "matchmaking",
+                    "tournament",
+                    "shared_game",
+                    "bot",
+                    "bind",
+                    "interface",
+                    "bind_all",
+                    "tls_cert",
+                    "reconnect_grace",
+                    "spectators",
+                    "queue_challengers",
+                ]),
             clap::Arg::new("playboard_size")
                 .short('s')
                 .long("size")
@@ -19,14 +98,470 @@ fn get_args() -> clap::ArgMatches {
                 .help("Size of playboard edge")
                 .value_parser(3..=10)
                 .default_value("3"),
-        ])
-        .get_matches()
+            clap::Arg::new("win_length")
+                .short('w')
+                .long("win-length")
+                .required(false)
+                .help(
+                    "Number of fields in line required for win (size of playboard edge if not set)",
+                )
+                .value_parser(3..=10),
+            clap::Arg::new("accounts")
+                .long("accounts")
+                .required(false)
+                .help("File with accounts of players - clients have to log in, register or continue as guest")
+                .value_parser(clap::value_parser!(std::path::PathBuf)),
+            clap::Arg::new("ratings")
+                .long("ratings")
+                .required(false)
+                .help("File with ratings of logged in players")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .requires("accounts"),
+            clap::Arg::new("rooms")
+                .long("rooms")
+                .required(false)
+                .help("Host multiple rooms, where size of playboard is chosen by creator of room")
+                .action(clap::ArgAction::SetTrue),
+            clap::Arg::new("matchmaking")
+                .long("matchmaking")
+                .required(false)
+                .help("Host multiple rooms and pair connected clients automatically")
+                .action(clap::ArgAction::SetTrue),
+            clap::Arg::new("rating_range")
+                .long("rating-range")
+                .required(false)
+                .help("Maximal difference of ratings of clients paired by matchmaking")
+                .value_parser(clap::value_parser!(u32)),
+            clap::Arg::new("tournament")
+                .long("tournament")
+                .required(false)
+                .help("File with participants of tournament - account name or \"bot <name>\" on each line")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .conflicts_with_all(["rooms", "matchmaking"]),
+            clap::Arg::new("tournament_format")
+                .long("tournament-format")
+                .required(false)
+                .help("System generating pairings of tournament rounds")
+                .value_parser(["round-robin", "swiss"])
+                .default_value("round-robin"),
+            clap::Arg::new("rounds")
+                .long("rounds")
+                .required(false)
+                .help("Number of rounds of swiss tournament (enough rounds for single winner if not set)")
+                .value_parser(clap::value_parser!(u64).range(1..)),
+            clap::Arg::new("tournament_start")
+                .long("tournament-start")
+                .required(false)
+                .help("Time in seconds of waiting for participants before first round")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("60"),
+            clap::Arg::new("report")
+                .long("report")
+                .required(false)
+                .help("File for text report of finished tournament")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .requires("tournament"),
+            clap::Arg::new("json_report")
+                .long("json-report")
+                .required(false)
+                .help("File for JSON report of finished tournament")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .requires("tournament"),
+            clap::Arg::new("pie_rule")
+                .long("pie-rule")
+                .required(false)
+                .help("Allow second player to swap sides after first move")
+                .action(clap::ArgAction::SetTrue),
+            clap::Arg::new("starting_player")
+                .long("starting-player")
+                .required(false)
+                .help("Policy selecting player starting new game")
+                .value_parser(["alternate", "loser", "winner", "random"])
+                .default_value("alternate"),
+            clap::Arg::new("seed")
+                .long("seed")
+                .required(false)
+                .help("Seed for random selection of starting player")
+                .value_parser(clap::value_parser!(u64)),
+            clap::Arg::new("best_of")
+                .long("best-of")
+                .required(false)
+                .help("Number of games in match (endless match if not set)")
+                .value_parser(clap::value_parser!(u64).range(1..)),
+            clap::Arg::new("move_time")
+                .long("move-time")
+                .required(false)
+                .help("Time limit for each move in seconds")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .conflicts_with("clock"),
+            clap::Arg::new("clock")
+                .long("clock")
+                .required(false)
+                .help("Total time of each player for game in seconds")
+                .value_parser(clap::value_parser!(u64).range(1..)),
+            clap::Arg::new("increment")
+                .long("increment")
+                .required(false)
+                .help("Time in seconds added to clock after each move")
+                .value_parser(clap::value_parser!(u64))
+                .requires("clock")
+                .default_value("0"),
+            clap::Arg::new("clock_updates")
+                .long("clock-updates")
+                .required(false)
+                .help("Period in seconds of sending remaining time to players")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .default_value("15"),
+            clap::Arg::new("reconnect_grace")
+                .long("reconnect-grace")
+                .required(false)
+                .help("Time in seconds for reconnection of disconnected player")
+                .value_parser(clap::value_parser!(u64).range(1..)),
+            clap::Arg::new("spectators")
+                .long("spectators")
+                .required(false)
+                .help("Maximal number of spectators watching game")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("5"),
+            clap::Arg::new("queue_challengers")
+                .long("queue-challengers")
+                .required(false)
+                .help("Queue connections over limit as next challengers instead of rejecting them")
+                .action(clap::ArgAction::SetTrue),
+        ]);
+
+    #[cfg(unix)]
+    {
+        command = command.arg(
+            clap::Arg::new("unix_socket")
+                .long("unix-socket")
+                .required(false)
+                .help("Path of Unix domain socket for clients playing own game alongside TCP clients (only Unix socket is used if no network option is set)")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .conflicts_with("local"),
+        );
+    }
+
+    let args = command.get_matches_mut();
+
+    // Win length is limited by size of playboard
+    if let (Some(size), Some(win_length)) = (
+        args.get_one::<i64>("playboard_size"),
+        args.get_one::<i64>("win_length"),
+    ) {
+        if win_length > size {
+            command
+                .error(
+                    clap::error::ErrorKind::ValueValidation,
+                    format!(
+                        "--win-length {} is greater than --size {}",
+                        win_length, size
+                    ),
+                )
+                .exit();
+        }
+    }
+
+    args
+}
+
+/// Get policy selecting player starting new game from user arguments
+fn get_starting_policy(args: &clap::ArgMatches) -> game::starting_policy::StartingPolicy {
+    match args.get_one::<String>("starting_player").unwrap().as_str() {
+        "loser" => game::starting_policy::StartingPolicy::LoserStarts,
+        "winner" => game::starting_policy::StartingPolicy::WinnerStarts,
+        "random" => {
+            let seed = match args.get_one::<u64>("seed") {
+                Some(seed) => seed.to_owned(),
+                None => std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|duration| duration.as_nanos() as u64)
+                    .unwrap_or_default(),
+            };
+            log::info!("Starting player is selected randomly with seed {}", seed);
+            game::starting_policy::StartingPolicy::Random(seed)
+        }
+        _ => game::starting_policy::StartingPolicy::Alternate,
+    }
+}
+
+/// Get time limits of players from user arguments
+fn get_time_control(args: &clap::ArgMatches) -> game::clock::TimeControl {
+    let seconds = |name| {
+        args.get_one::<u64>(name)
+            .map(|x| std::time::Duration::from_secs(x.to_owned()))
+    };
+
+    match (seconds("move_time"), seconds("clock")) {
+        (Some(move_time), _) => game::clock::TimeControl::PerMove(move_time),
+        (None, Some(initial)) => game::clock::TimeControl::Clock {
+            initial,
+            increment: seconds("increment").unwrap_or_default(),
+        },
+        (None, None) => game::clock::TimeControl::Unlimited,
+    }
+}
+
+/// Get tournament settings from user arguments - None if no tournament is required
+fn get_tournament_settings(
+    args: &clap::ArgMatches,
+    accounts_enabled: bool,
+) -> Result<Option<game::tournament::Settings>, String> {
+    let path = match args.get_one::<std::path::PathBuf>("tournament") {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| format!("Unable to load participants from {:?}: {}", path, err))?;
+    let participants = game::tournament::parse_participants(&content)
+        .map_err(|err| format!("Unable to load participants from {:?}: {}", path, err))?;
+
+    // Human participants are identified by their accounts
+    if !accounts_enabled
+        && participants
+            .iter()
+            .any(|x| matches!(x, game::tournament::Participant::Human(_)))
+    {
+        return Err("Tournament with human participants requires accounts".to_owned());
+    }
+
+    let format = match args
+        .get_one::<String>("tournament_format")
+        .unwrap()
+        .as_str()
+    {
+        "swiss" => game::tournament::standings::Format::Swiss(
+            args.get_one::<u64>("rounds")
+                .map(|x| x.to_owned() as usize)
+                .unwrap_or_else(|| game::tournament::standings::swiss_rounds(participants.len())),
+        ),
+        _ => game::tournament::standings::Format::RoundRobin,
+    };
+
+    Ok(Some(game::tournament::Settings {
+        participants,
+        format,
+        start_timeout: std::time::Duration::from_secs(
+            args.get_one::<u64>("tournament_start").unwrap().to_owned(),
+        ),
+    }))
+}
+
+/// Write report of finished tournament to file
+fn write_report(args: &clap::ArgMatches, name: &str, report: String) {
+    if let Some(path) = args.get_one::<std::path::PathBuf>(name) {
+        match std::fs::write(path, report) {
+            Ok(()) => log::info!("Report of tournament saved to {:?}", path),
+            Err(err) => log::error!("Unable to save report to {:?}: {}", path, err),
+        }
+    }
+}
+
+/// Network of listening sockets required by user (None if user should be asked)
+fn get_bind_target(args: &clap::ArgMatches) -> Option<game::player_manager::tcp::BindTarget> {
+    if let Some(addr) = args.get_one::<game::player_manager::tcp::ListenAddress>("bind") {
+        return Some(game::player_manager::tcp::BindTarget::Address(*addr));
+    }
+
+    if let Some(name) = args.get_one::<String>("interface") {
+        return Some(game::player_manager::tcp::BindTarget::Interface(
+            name.to_owned(),
+        ));
+    }
+
+    match args.get_flag("bind_all") {
+        true => Some(game::player_manager::tcp::BindTarget::All),
+        false => None,
+    }
+}
+
+/// Some option of network listeners was set by user
+fn is_network_required(args: &clap::ArgMatches) -> bool {
+    args.get_flag("bind_all")
+        || [
+            "port",
+            "bind",
+            "interface",
+            "websocket_port",
+            "http_port",
+            "tls_cert",
+        ]
+        .into_iter()
+        .any(|id| args.contains_id(id))
+}
+
+/// Player manager of clients of Unix socket required by user
+#[cfg(unix)]
+fn get_unix_player_manager(
+    args: &clap::ArgMatches,
+    accounts: Option<std::sync::Arc<game::accounts::AccountStore>>,
+) -> Option<game::player_manager::tcp::PlayerManager> {
+    let path = args.get_one::<std::path::PathBuf>("unix_socket")?;
+
+    match game::player_manager::unix::from_path(path, accounts) {
+        Ok(x) => Some(x),
+        Err(err) => {
+            log::error!(
+                "Unable to create Unix socket player manager due to following error: {}",
+                err
+            );
+            std::process::exit(2)
+        }
+    }
+}
+
+/// Unix sockets are not supported on other platforms
+#[cfg(not(unix))]
+fn get_unix_player_manager(
+    _args: &clap::ArgMatches,
+    _accounts: Option<std::sync::Arc<game::accounts::AccountStore>>,
+) -> Option<game::player_manager::tcp::PlayerManager> {
+    None
+}
+
+/// Player managers of TCP clients and optional WebSocket and HTTP clients on network selected
+/// by user
+async fn create_network_player_managers(
+    args: &clap::ArgMatches,
+    accounts: Option<std::sync::Arc<game::accounts::AccountStore>>,
+) -> (
+    game::player_manager::tcp::PlayerManager,
+    Option<game::player_manager::websocket::PlayerManager>,
+    Option<game::player_manager::http::PlayerManager>,
+) {
+    let ip_address = match game::player_manager::tcp::select_network(get_bind_target(args)) {
+        Ok(addr) => addr,
+        Err(()) => std::process::exit(1),
+    };
+
+    let websocket_player_manager = match args.get_one::<u16>("websocket_port") {
+        Some(port) => {
+            let addr = ip_address.with_port(port.to_owned());
+            match game::player_manager::websocket::PlayerManager::from_socket_address(
+                addr,
+                accounts.clone(),
+            )
+            .await
+            {
+                Ok(x) => Some(x),
+                Err(err) => {
+                    log::error!(
+                        "Unable to create WebSocket player manager due to following error: {}",
+                        err
+                    );
+                    std::process::exit(2)
+                }
+            }
+        }
+        None => None,
+    };
+
+    let http_player_manager = match args.get_one::<u16>("http_port") {
+        Some(port) => {
+            let addr = ip_address.with_port(port.to_owned());
+            match game::player_manager::http::PlayerManager::from_socket_address(
+                addr,
+                accounts.clone(),
+            )
+            .await
+            {
+                Ok(x) => Some(x),
+                Err(err) => {
+                    log::error!(
+                        "Unable to create HTTP player manager due to following error: {}",
+                        err
+                    );
+                    std::process::exit(2)
+                }
+            }
+        }
+        None => None,
+    };
+
+    let tls = match get_tls_acceptor(args, ip_address.ip) {
+        Ok(x) => x,
+        Err(err) => {
+            log::error!("Unable to set up TLS due to following error: {}", err);
+            std::process::exit(2)
+        }
+    };
+
+    let r_player_manager = match args.get_one::<u16>("port") {
+        Some(port) => {
+            let addr = ip_address.with_port(port.to_owned());
+            game::player_manager::tcp::PlayerManager::from_socket_address(addr, tls, accounts).await
+        }
+        None => game::player_manager::tcp::PlayerManager::from_ip(ip_address, tls, accounts).await,
+    };
+
+    let player_manager = match r_player_manager {
+        Ok(x) => x,
+        Err(err) => {
+            log::error!(
+                "Unable to create TCP player manager due to following error: {}",
+                err
+            );
+            std::process::exit(2)
+        }
+    };
+
+    (
+        player_manager,
+        websocket_player_manager,
+        http_player_manager,
+    )
+}
+
+/// Wait for request to stop server (Ctrl+C or SIGTERM)
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(x) => x,
+                Err(err) => {
+                    log::error!("Unable to handle SIGTERM: {}", err);
+                    let _ = tokio::signal::ctrl_c().await;
+                    return;
+                }
+            };
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => (),
+            _ = terminate.recv() => (),
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Load (or generate self-signed) TLS certificate of TCP player manager
+fn get_tls_acceptor(
+    args: &clap::ArgMatches,
+    ip: std::net::IpAddr,
+) -> Result<Option<tokio_rustls::TlsAcceptor>, std::io::Error> {
+    let (cert, key) = match (
+        args.get_one::<std::path::PathBuf>("tls_cert"),
+        args.get_one::<std::path::PathBuf>("tls_key"),
+    ) {
+        (Some(cert), Some(key)) => (cert, key),
+        _ => return Ok(None),
+    };
+
+    if args.get_flag("generate_cert") && !cert.exists() && !key.exists() {
+        game::player_manager::tcp::tls::generate_self_signed(cert, key, ip)?;
+        log::info!("Self-signed certificate saved to {:?}", cert);
+    }
+
+    game::player_manager::tcp::tls::load_acceptor(cert, key).map(Some)
 }
 
 /// Set logger
-fn set_logger() -> Result<(), log::SetLoggerError> {
+fn set_logger(level: simplelog::LevelFilter) -> Result<(), log::SetLoggerError> {
     simplelog::CombinedLogger::init(vec![simplelog::TermLogger::new(
-        simplelog::LevelFilter::Info,
+        level,
         simplelog::Config::default(),
         simplelog::TerminalMode::Mixed,
         simplelog::ColorChoice::Auto,
@@ -37,46 +572,282 @@ fn set_logger() -> Result<(), log::SetLoggerError> {
 async fn main() {
     let args = get_args();
 
-    // Set logger
-    match set_logger() {
+    // Terminal read by local players can not be cancelled, so local play is stopped by signal
+    // without cleanup
+    if args.get_flag("local") {
+        return run(args).await;
+    }
+
+    // Server is stopped by returning, so listeners clean up (e.g. remove Unix socket)
+    tokio::select! {
+        _ = run(args) => (),
+        _ = shutdown_signal() => log::info!("Server is shutting down"),
+    }
+}
+
+/// Run game (or lobby or tournament) required by user arguments
+async fn run(args: clap::ArgMatches) {
+    // Set logger - log of local game would be mixed with messages to players
+    let log_level = match args.get_flag("local") {
+        true => simplelog::LevelFilter::Warn,
+        false => simplelog::LevelFilter::Info,
+    };
+    match set_logger(log_level) {
         Ok(()) => (),
         Err(_) => std::process::exit(1),
     }
 
     log::info!("Starting game");
 
-    let ip_address = match game::player_manager::tcp::select_network() {
-        Ok(addr) => addr,
-        Err(()) => std::process::exit(1),
+    if let Some(name) = args.get_one::<String>("bot") {
+        if let Err(err) = game::nickname::validate(name, std::iter::empty()) {
+            log::error!("Invalid name of bot \"{}\" ({:?})", name, err);
+            std::process::exit(2)
+        }
+    }
+
+    let accounts = match args.get_one::<std::path::PathBuf>("accounts") {
+        Some(path) => match game::accounts::AccountStore::open(path) {
+            Ok(x) => Some(std::sync::Arc::new(x)),
+            Err(err) => {
+                log::error!("Unable to load accounts from {:?}: {}", path, err);
+                std::process::exit(2)
+            }
+        },
+        None => None,
     };
 
-    let r_player_manager = match args.get_one::<u16>("port") {
-        Some(port) => {
-            let addr = std::net::SocketAddr::new(ip_address, port.to_owned());
-            game::player_manager::tcp::PlayerManager::from_socket_address(addr).await
-        }
-        None => game::player_manager::tcp::PlayerManager::from_ip(ip_address).await,
+    let ratings = match args.get_one::<std::path::PathBuf>("ratings") {
+        Some(path) => match game::rating::RatingStore::open(path) {
+            Ok(x) => Some(std::sync::Arc::new(x)),
+            Err(err) => {
+                log::error!("Unable to load ratings from {:?}: {}", path, err);
+                std::process::exit(2)
+            }
+        },
+        None => None,
     };
 
-    let player_manager = match r_player_manager {
+    let tournament_settings = match get_tournament_settings(&args, accounts.is_some()) {
         Ok(x) => x,
         Err(err) => {
-            log::error!(
-                "Unable to create TCP player manager due to following error: {}",
-                err
-            );
+            log::error!("{}", err);
             std::process::exit(2)
         }
     };
 
     let playboard_size = args.get_one::<i64>("playboard_size").unwrap().to_owned() as usize;
-    let playboard_builder = move || game::playboard::pb_n_n::Playboard::new(playboard_size);
+    let win_length = args
+        .get_one::<i64>("win_length")
+        .map(|x| x.to_owned() as usize);
+    let playboard_builder = move || match win_length {
+        Some(x) => game::playboard::pb_n_n::Playboard::with_win_length(playboard_size, x),
+        None => game::playboard::pb_n_n::Playboard::new(playboard_size),
+    };
+
+    let settings = game::Settings {
+        pie_rule: args.get_flag("pie_rule"),
+        starting_policy: get_starting_policy(&args),
+        match_length: args
+            .get_one::<u64>("best_of")
+            .map(|x| x.to_owned() as usize),
+        time_control: get_time_control(&args),
+        clock_updates: args
+            .get_one::<u64>("clock_updates")
+            .map(|x| std::time::Duration::from_secs(x.to_owned())),
+        reconnect_grace: args
+            .get_one::<u64>("reconnect_grace")
+            .map(|x| std::time::Duration::from_secs(x.to_owned())),
+        spectator_limit: args.get_one::<u64>("spectators").unwrap().to_owned() as usize,
+        queue_challengers: args.get_flag("queue_challengers"),
+        ratings: ratings.map(|store| game::rating::RatedPool {
+            store,
+            pool: game::rating::Pool {
+                size: playboard_size,
+                win_length: win_length.unwrap_or(playboard_size),
+            },
+        }),
+        results: None,
+    };
+
+    if args.get_flag("local") {
+        let player_manager = game::player_manager::local::PlayerManager::new();
+        let finished = player_manager.get_finished();
+
+        tokio::select! {
+            _ = game::run_game(
+                player_manager,
+                settings,
+                playboard_builder,
+                game::converters::pm_tcp_msg_to_command,
+                game::converters::pb_n_n_to_rendered,
+            ) => (),
+            _ = finished.notified() => (),
+        }
+        return;
+    }
+
+    // Unix socket alone does not need any network
+    let unix_only_player_manager = match is_network_required(&args) {
+        true => None,
+        false => get_unix_player_manager(&args, accounts.clone()),
+    };
+
+    let (player_manager, websocket_player_manager, http_player_manager, unix_player_manager) =
+        match unix_only_player_manager {
+            Some(x) => (x, None, None, None),
+            None => {
+                let (player_manager, websocket_player_manager, http_player_manager) =
+                    create_network_player_managers(&args, accounts.clone()).await;
+                (
+                    player_manager,
+                    websocket_player_manager,
+                    http_player_manager,
+                    get_unix_player_manager(&args, accounts),
+                )
+            }
+        };
+
+    // Clients of other player managers join game of TCP clients or play own games
+    let mut shared_player_manager = game::player_manager::composite::PlayerManager::new();
+
+    match args.get_flag("shared_game") {
+        true => {
+            if let Some(websocket_player_manager) = websocket_player_manager {
+                shared_player_manager.add(websocket_player_manager);
+            }
+            if let Some(http_player_manager) = http_player_manager {
+                shared_player_manager.add(http_player_manager);
+            }
+            if let Some(unix_player_manager) = unix_player_manager {
+                shared_player_manager.add(unix_player_manager);
+            }
+        }
+        false => {
+            if let Some(websocket_player_manager) = websocket_player_manager {
+                tokio::spawn(game::run_game(
+                    websocket_player_manager,
+                    settings.clone(),
+                    playboard_builder,
+                    game::converters::pm_json_msg_to_command,
+                    game::converters::pb_n_n_to_rendered,
+                ));
+            }
+
+            if let Some(http_player_manager) = http_player_manager {
+                tokio::spawn(game::run_game(
+                    http_player_manager,
+                    settings.clone(),
+                    playboard_builder,
+                    game::converters::pm_json_msg_to_command,
+                    game::converters::pb_n_n_to_board,
+                ));
+            }
+
+            if let Some(unix_player_manager) = unix_player_manager {
+                tokio::spawn(game::run_game(
+                    unix_player_manager,
+                    settings.clone(),
+                    playboard_builder,
+                    game::converters::pm_tcp_msg_to_command,
+                    game::converters::pb_n_n_to_rendered,
+                ));
+            }
+        }
+    }
+
+    if let Some(tournament_settings) = tournament_settings {
+        let create_room = move |room_player_manager, results| {
+            // Tournament games are single unrated games
+            let mut settings = settings.clone();
+            settings.match_length = Some(1);
+            settings.reconnect_grace = None;
+            settings.ratings = None;
+            settings.results = Some(results);
+
+            tokio::spawn(game::run_game(
+                room_player_manager,
+                settings,
+                playboard_builder,
+                game::converters::pm_tcp_msg_to_command,
+                game::converters::pb_n_n_to_rendered,
+            ))
+        };
+        let create_bot =
+            move |name: &str| game::player_manager::tcp::Connection::bot(name, playboard_size);
+
+        let tournament = game::tournament::run_tournament(
+            player_manager,
+            tournament_settings,
+            create_room,
+            create_bot,
+        )
+        .await;
+
+        log::info!("Results of tournament\n{}", tournament.to_text_report());
+        write_report(&args, "report", tournament.to_text_report());
+        write_report(&args, "json_report", tournament.to_json_report());
+        return;
+    }
+
+    shared_player_manager.add(player_manager);
+
+    if let Some(name) = args.get_one::<String>("bot") {
+        shared_player_manager.add(game::player_manager::tcp::PlayerManager::from_bot(
+            name,
+            playboard_size,
+        ));
+    }
+
+    if args.get_flag("rooms") || args.get_flag("matchmaking") {
+        let lobby_settings = game::lobby::Settings {
+            matchmaking: match args.get_flag("matchmaking") {
+                true => Some(playboard_size),
+                false => None,
+            },
+            rating_range: args.get_one::<u32>("rating_range").copied(),
+            ratings: settings.ratings.as_ref().map(|x| x.store.clone()),
+        };
+
+        let create_room = move |options: game::lobby::RoomOptions, room_player_manager| {
+            // Each room configuration has own ratings
+            let mut settings = settings.clone();
+            if let Some(ratings) = &mut settings.ratings {
+                ratings.pool = game::rating::Pool {
+                    size: options.size,
+                    win_length: options.win_length,
+                };
+            }
+
+            tokio::spawn(game::run_game(
+                room_player_manager,
+                settings,
+                move || {
+                    game::playboard::pb_n_n::Playboard::with_win_length(
+                        options.size,
+                        options.win_length,
+                    )
+                },
+                game::converters::pm_tcp_msg_to_command,
+                game::converters::pb_n_n_to_rendered,
+            ))
+        };
+
+        return game::lobby::run_lobby(
+            shared_player_manager,
+            lobby_settings,
+            create_room,
+            game::converters::pm_tcp_msg_to_lobby_command,
+        )
+        .await;
+    }
 
     game::run_game(
-        player_manager,
+        shared_player_manager,
+        settings,
         playboard_builder,
-        game::converters::pm_tcp_msg_to_x_y,
-        game::converters::pb_n_n_to_string,
+        game::converters::pm_tcp_msg_to_command,
+        game::converters::pb_n_n_to_rendered,
     )
     .await;
 }